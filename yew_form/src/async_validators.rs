@@ -0,0 +1,321 @@
+//! Validators that need a round trip, e.g. to check that a user name isn't taken yet.
+//!
+//! They are registered per field path pattern with [`Form::set_async_validator`](crate::Form::set_async_validator).
+//! A check starts once the field passes its other validation and its value has been left unchanged for the delay
+//! of the validator. While it runs the field is [`pending`](crate::form_field::FormField::pending),
+//! a check of a value that changed in the meantime is aborted.
+//! Results are kept per value, so going back to a value that was checked doesn't check it again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures::channel::oneshot;
+use futures::future::{AbortHandle, Abortable, Aborted, LocalBoxFuture};
+use futures::FutureExt;
+use validator::ValidationError;
+use yew::AttrValue;
+
+use crate::model::matches_field_pattern;
+
+/// Checks a value, the error makes the field invalid like the errors of `validator`
+pub type AsyncCheck = LocalBoxFuture<'static, Result<(), ValidationError>>;
+
+/// A running check, which resolves to [`Aborted`] if it is cancelled
+pub(crate) type RunningCheck =
+    LocalBoxFuture<'static, Result<Result<(), ValidationError>, Aborted>>;
+
+#[derive(Clone)]
+pub(crate) struct AsyncValidator {
+    delay: Duration,
+    check: Rc<dyn Fn(AttrValue) -> AsyncCheck>,
+}
+
+impl AsyncValidator {
+    pub(crate) fn new<F, Fut>(delay: Duration, check: F) -> Self
+    where
+        F: Fn(AttrValue) -> Fut + 'static,
+        Fut: Future<Output = Result<(), ValidationError>> + 'static,
+    {
+        AsyncValidator {
+            delay,
+            check: Rc::new(move |value| check(value).boxed_local()),
+        }
+    }
+}
+
+struct Running {
+    id: u64,
+    value: AttrValue,
+    abort: AbortHandle,
+}
+
+/// The async validators of a form, their running checks and the results of the finished ones
+#[derive(Default)]
+pub(crate) struct AsyncValidators {
+    /// Field path patterns, where `*` matches any single segment
+    validators: Vec<(AttrValue, AsyncValidator)>,
+    running: HashMap<AttrValue, Running>,
+    /// The last value checked for a field, with the error of the check
+    results: HashMap<AttrValue, (AttrValue, Option<ValidationError>)>,
+    /// Notified once no check is running
+    waiters: Vec<oneshot::Sender<()>>,
+    next_id: u64,
+}
+
+impl PartialEq for AsyncValidators {
+    fn eq(&self, other: &Self) -> bool {
+        self.results == other.results
+            && self.running.len() == other.running.len()
+            && self.running.iter().all(|(path, running)| {
+                matches!(other.running.get(path), Some(other) if other.id == running.id)
+            })
+            && self.validators.len() == other.validators.len()
+            && self
+                .validators
+                .iter()
+                .zip(&other.validators)
+                .all(|((a, v), (b, w))| a == b && Rc::ptr_eq(&v.check, &w.check))
+    }
+}
+
+impl AsyncValidators {
+    /// Registers `validator` for the fields matching `pattern`,
+    /// dropping the results and cancelling the checks of the validator they had before
+    pub(crate) fn set(&mut self, pattern: AttrValue, validator: AsyncValidator) {
+        self.results
+            .retain(|field_path, _| !matches_field_pattern(&pattern, field_path));
+        self.cancel_unless(|field_path| !matches_field_pattern(&pattern, field_path));
+        self.validators.retain(|(p, _)| *p != pattern);
+        self.validators.push((pattern, validator));
+    }
+
+    /// Returns true if a validator applies to `field_path`
+    pub(crate) fn has(&self, field_path: &str) -> bool {
+        self.get(field_path).is_some()
+    }
+
+    fn get(&self, field_path: &str) -> Option<&AsyncValidator> {
+        self.validators
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches_field_pattern(pattern, field_path))
+            .map(|(_, validator)| validator)
+    }
+
+    /// Starts checking `value`, cancelling the running check of another value of the field.
+    /// Returns [`None`] if the field has no validator or the value is already checked or being checked.
+    pub(crate) fn start(
+        &mut self,
+        field_path: &str,
+        value: AttrValue,
+    ) -> Option<(u64, RunningCheck)> {
+        if matches!(self.running.get(field_path), Some(running) if running.value == value) {
+            return None;
+        }
+
+        self.cancel(field_path);
+
+        if matches!(self.results.get(field_path), Some((checked, _)) if *checked == value) {
+            return None;
+        }
+
+        let AsyncValidator { delay, check } = self.get(field_path)?.clone();
+        let (abort, registration) = AbortHandle::new_pair();
+        let id = self.next_id;
+        let checked = value.clone();
+
+        self.next_id += 1;
+        self.running
+            .insert(field_path.to_owned().into(), Running { id, value, abort });
+
+        let future = async move {
+            // Debounces the check, a new value aborts the check while it waits
+            if !delay.is_zero() {
+                yew::platform::time::sleep(delay).await;
+            }
+
+            check(checked).await
+        };
+
+        Some((id, Abortable::new(future, registration).boxed_local()))
+    }
+
+    pub(crate) fn cancel(&mut self, field_path: &str) {
+        if let Some(running) = self.running.remove(field_path) {
+            running.abort.abort();
+            self.notify();
+        }
+    }
+
+    /// The results of the finished checks by field path, moved along when the rows of a list move
+    pub(crate) fn results_mut(
+        &mut self,
+    ) -> &mut HashMap<AttrValue, (AttrValue, Option<ValidationError>)> {
+        &mut self.results
+    }
+
+    /// Cancels the running checks of the fields for which `keep` returns `false`
+    pub(crate) fn cancel_unless(&mut self, keep: impl Fn(&str) -> bool) {
+        let cancelled: Vec<AttrValue> = self
+            .running
+            .keys()
+            .filter(|field_path| !keep(field_path))
+            .cloned()
+            .collect();
+
+        for field_path in cancelled {
+            self.cancel(&field_path);
+        }
+    }
+
+    /// Keeps the result of the check `id`, returns `false` if the check was cancelled
+    pub(crate) fn finish(
+        &mut self,
+        field_path: &str,
+        id: u64,
+        error: Option<ValidationError>,
+    ) -> bool {
+        match self.running.get(field_path) {
+            Some(running) if running.id == id => {
+                let running = self.running.remove(field_path).expect("Check was found");
+                self.results
+                    .insert(field_path.to_owned().into(), (running.value, error));
+                self.notify();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn is_running(&self, field_path: &str) -> bool {
+        self.running.contains_key(field_path)
+    }
+
+    /// The error found when `value` was checked, if it was
+    pub(crate) fn error(&self, field_path: &str, value: &str) -> Option<&ValidationError> {
+        match self.results.get(field_path) {
+            Some((checked, error)) if checked == value => error.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Resolves once no check is running, or [`None`] if none is running
+    pub(crate) fn wait(&mut self) -> Option<oneshot::Receiver<()>> {
+        (!self.running.is_empty()).then(|| {
+            let (sender, receiver) = oneshot::channel();
+            self.waiters.push(sender);
+            receiver
+        })
+    }
+
+    fn notify(&mut self) {
+        if self.running.is_empty() {
+            for waiter in self.waiters.drain(..) {
+                let _ = waiter.send(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncValidator;
+    use crate::fixtures::{invoice, line, Signup};
+    use crate::form_state::FormState;
+    use futures::executor::block_on;
+    use futures::future::Aborted;
+    use std::time::Duration;
+    use validator::ValidationError;
+    use yew::AttrValue;
+
+    #[test]
+    fn test_async_validator() {
+        let mut state = FormState::new(Signup { login: "".into() });
+        state.set_async_validator(
+            "login".into(),
+            AsyncValidator::new(Duration::ZERO, |login: AttrValue| async move {
+                match login.as_str() {
+                    "admin" => Err(ValidationError::new("taken")),
+                    _ => Ok(()),
+                }
+            }),
+        );
+
+        state.set_value("login", "ad");
+        assert!(state.start_checks(Some("login")).is_empty());
+
+        state.set_value("login", "admin");
+        let (_, stale_id, stale) = state.start_checks(Some("login")).remove(0);
+        assert!(state.field("login").pending());
+        assert!(!state.valid());
+
+        state.set_value("login", "bob");
+        let (_, id, check) = state.start_checks(Some("login")).remove(0);
+        assert_eq!(block_on(stale), Err(Aborted));
+        assert!(!state.finish_check("login", stale_id, None));
+        assert!(state.finish_check("login", id, block_on(check).unwrap().err()));
+        assert!(state.valid());
+
+        state.set_value("login", "admin");
+        let (_, id, check) = state.start_checks(Some("login")).remove(0);
+        assert!(state.finish_check("login", id, block_on(check).unwrap().err()));
+        assert!(!state.field_valid("login"));
+        assert_eq!(
+            state.field("login").validation_error().unwrap().code,
+            "taken"
+        );
+
+        // The result of a value that was checked is kept
+        state.set_value("login", "bob");
+        state.set_value("login", "admin");
+        assert!(state.start_checks(Some("login")).is_empty());
+        assert!(!state.field_valid("login"));
+        assert!(!state.field("login").pending());
+
+        // A new validator checks the value again
+        let fields = state.set_async_validator(
+            "login".into(),
+            AsyncValidator::new(Duration::ZERO, |_| async { Ok(()) }),
+        );
+        assert_eq!(fields, vec![AttrValue::from("login")]);
+        assert!(state.field_valid("login"));
+        let (_, id, check) = state.start_checks(Some("login")).remove(0);
+        assert!(state.finish_check("login", id, block_on(check).unwrap().err()));
+        assert!(state.valid());
+    }
+
+    #[test]
+    fn test_async_validator_rows() {
+        let mut state = FormState::new(invoice(&[("a", 1), ("b", 2)]));
+        state.set_async_validator(
+            "lines.*.name".into(),
+            AsyncValidator::new(Duration::ZERO, |name: AttrValue| async move {
+                match name.as_str() {
+                    "taken" => Err(ValidationError::new("taken")),
+                    _ => Ok(()),
+                }
+            }),
+        );
+
+        state.set_value("lines.1.name", "taken");
+        let (_, _, removed) = state.start_checks(Some("lines.1.name")).remove(0);
+        state.remove("lines", 1).unwrap();
+        assert_eq!(block_on(removed), Err(Aborted));
+
+        state.push("lines", line("taken", 3)).unwrap();
+        let checks = state.start_checks(Some("lines"));
+        assert_eq!(checks.len(), 2);
+        for (field, id, check) in checks {
+            assert!(state.finish_check(&field, id, block_on(check).unwrap().err()));
+        }
+        assert!(!state.field_valid("lines.1.name"));
+
+        // The results move with the rows
+        state.swap("lines", 0, 1).unwrap();
+        assert!(!state.field_valid("lines.0.name"));
+        assert!(state.field_valid("lines.1.name"));
+        assert!(state.start_checks(Some("lines")).is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+use yew::prelude::*;
+
+use crate::components::{CheckBox, File, Input, Select, SelectOption};
+use crate::form::Form;
+use crate::model::{
+    join_field_path, matches_field_pattern, split_field_path, ValueKind, PRESENT_FIELD,
+    VARIANT_FIELD,
+};
+use crate::{FieldMeta, Model};
+
+/// Customizes how an [`AutoForm`] renders its fields.
+/// Fields are selected with path patterns, where a `*` segment matches any index or key.
+/// For the order and the hidden fields, a pattern matching a nested struct or collection applies to all its fields.
+#[derive(Clone, Default, PartialEq)]
+pub struct AutoFormOverrides {
+    widgets: Vec<(AttrValue, Callback<AttrValue, Html>)>,
+    order: Vec<AttrValue>,
+    hidden: Vec<AttrValue>,
+}
+
+impl AutoFormOverrides {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Renders the fields matching `pattern` with `render`, which is given the path of the field.
+    /// A pattern matching a nested struct replaces its whole fieldset.
+    pub fn widget(
+        mut self,
+        pattern: impl Into<AttrValue>,
+        render: impl Fn(AttrValue) -> Html + 'static,
+    ) -> Self {
+        self.widgets.push((pattern.into(), Callback::from(render)));
+        self
+    }
+
+    /// Renders the fields matching `patterns` first, in the order of the patterns.
+    /// The other fields follow in the order they are declared.
+    pub fn order<P: Into<AttrValue>>(mut self, patterns: impl IntoIterator<Item = P>) -> Self {
+        self.order = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Doesn't render the fields matching `pattern`
+    pub fn hide(mut self, pattern: impl Into<AttrValue>) -> Self {
+        self.hidden.push(pattern.into());
+        self
+    }
+
+    fn widget_for(&self, field_path: &str) -> Option<&Callback<AttrValue, Html>> {
+        self.widgets
+            .iter()
+            .find(|(pattern, _)| matches_field_pattern(pattern, field_path))
+            .map(|(_, render)| render)
+    }
+
+    fn position(&self, field_path: &str) -> usize {
+        self.order
+            .iter()
+            .position(|pattern| matches_field_or_group(pattern, field_path))
+            .unwrap_or(self.order.len())
+    }
+
+    fn hidden(&self, field_path: &str) -> bool {
+        self.hidden
+            .iter()
+            .any(|pattern| matches_field_or_group(pattern, field_path))
+    }
+}
+
+/// Whether `pattern` matches `field_path` or the path of a value containing it, e.g. `address` for `address.city`
+fn matches_field_or_group(pattern: &str, field_path: &str) -> bool {
+    field_path
+        .match_indices('.')
+        .map(|(index, _)| &field_path[..index])
+        .chain([field_path])
+        .any(|path| matches_field_pattern(pattern, path))
+}
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct AutoFormProps<T: Model> {
+    pub form: Form<T>,
+    #[prop_or_default]
+    pub overrides: AutoFormOverrides,
+    #[prop_or_default]
+    pub classes: Classes,
+}
+
+/// Renders a control for every field of the model: an input for text, numbers and dates,
+/// a checkbox for booleans, a select for enums, a file input for [`FormFiles`](crate::FormFiles)
+/// and a fieldset for nested structs and collections.
+#[function_component(AutoForm)]
+pub fn auto_form<T: Model>(
+    AutoFormProps {
+        form,
+        overrides,
+        classes,
+    }: &AutoFormProps<T>,
+) -> Html {
+    let mut paths = vec![];
+
+    form.model().fields("", &mut paths);
+    paths.retain(|path| !overrides.hidden(path));
+    // Stable, so fields without a position keep their declaration order
+    paths.sort_by_key(|path| overrides.position(path));
+
+    html! {
+        <div class={classes.clone()}>
+            { render_fields(form, overrides, "", &paths) }
+        </div>
+    }
+}
+
+/// Renders `paths`, which all start with `prefix`, grouping the paths of nested values in fieldsets
+fn render_fields<T: Model>(
+    form: &Form<T>,
+    overrides: &AutoFormOverrides,
+    prefix: &str,
+    paths: &[AttrValue],
+) -> Html {
+    let mut segments: Vec<&str> = vec![];
+
+    for path in paths {
+        let (segment, _) = split_field_path(relative_path(prefix, path));
+
+        if !segments.contains(&segment) {
+            segments.push(segment);
+        }
+    }
+
+    segments
+        .into_iter()
+        .map(|segment| {
+            let path = join_field_path(prefix, segment);
+
+            if let Some(render) = overrides.widget_for(&path) {
+                return render.emit(path.into());
+            }
+
+            let nested: Vec<AttrValue> = paths
+                .iter()
+                .filter(|p| split_field_path(relative_path(prefix, p)).0 == segment)
+                .filter(|p| p.len() > path.len())
+                .cloned()
+                .collect();
+
+            if nested.is_empty() {
+                return render_field(form, path.into());
+            }
+
+            html! {
+                <fieldset name={path.clone()}>
+                    <legend>{ humanize(segment) }</legend>
+                    { render_fields(form, overrides, &path, &nested) }
+                </fieldset>
+            }
+        })
+        .collect()
+}
+
+fn render_field<T: Model>(form: &Form<T>, path: AttrValue) -> Html {
+    let meta = T::field_meta(&path).unwrap_or_else(|| FieldMeta::new(ValueKind::Text));
+    let (_, segment) = path.rsplit_once('.').unwrap_or(("", &path));
+    let label = meta
+        .label
+        .clone()
+        .unwrap_or_else(|| humanize(segment).into());
+
+    let control = match meta.kind {
+        ValueKind::Bool => html! {
+            <CheckBox<T> form={form} field_name={path.clone()} />
+        },
+        ValueKind::Options => html! {
+            <Select<T> form={form} field_name={path.clone()}>
+                if meta.optional {
+                    <SelectOption value="" />
+                }
+                { for meta.options.iter().map(|option| html_nested! {
+                    <SelectOption value={*option} />
+                }) }
+            </Select<T>>
+        },
+        ValueKind::Files => {
+            let multiple = form.file_rules(&path).and_then(|rules| rules.max_files) != Some(1);
+
+            html! {
+                <File<T> form={form} field_name={path.clone()} {multiple} />
+            }
+        }
+        _ => html! {
+            <Input<T> form={form} field_name={path.clone()} />
+        },
+    };
+
+    let field = form.field(&path);
+
+    html! {
+        <div class="auto-form-field">
+            <label for={path.clone()}>{ label }</label>
+            { control }
+            if let Some(help) = meta.help {
+                <small>{ help }</small>
+            }
+            if !field.valid() {
+                <p class="auto-form-message">{ field.message().clone() }</p>
+            }
+        </div>
+    }
+}
+
+fn relative_path<'a>(prefix: &str, path: &'a str) -> &'a str {
+    if prefix.is_empty() {
+        path
+    } else {
+        &path[prefix.len() + 1..]
+    }
+}
+
+/// A label for a path segment, e.g. `first_name` becomes "First name" and index `0` becomes "#1"
+fn humanize(segment: &str) -> String {
+    if segment == VARIANT_FIELD {
+        return "Variant".to_owned();
+    }
+
+    if segment == PRESENT_FIELD {
+        return "Present".to_owned();
+    }
+
+    if let Ok(index) = segment.parse::<usize>() {
+        return format!("#{}", index + 1);
+    }
+
+    let words = segment.replace('_', " ");
+    let mut chars = words.trim().chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use validator::Validate;
+    use yew::prelude::*;
+    use yew::ServerRenderer;
+
+    use super::{humanize, AutoForm, AutoFormOverrides};
+    use crate::fixtures::Address;
+    use crate::{use_form, FormFiles, FormValue, Model};
+
+    #[derive(FormValue, PartialEq, Clone, Debug)]
+    enum Plan {
+        Free,
+        Pro,
+    }
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    struct Signup {
+        name: String,
+        newsletter: bool,
+        plan: Plan,
+        #[validate]
+        address: Address,
+        avatar: FormFiles,
+    }
+
+    #[derive(Properties, PartialEq)]
+    struct SignupFormProps {
+        overrides: AutoFormOverrides,
+    }
+
+    #[function_component(SignupForm)]
+    fn signup_form(SignupFormProps { overrides }: &SignupFormProps) -> Html {
+        let form = use_form(|| Signup {
+            name: "".into(),
+            newsletter: false,
+            plan: Plan::Free,
+            address: Address { city: "".into() },
+            avatar: Default::default(),
+        });
+
+        html! {
+            <AutoForm<Signup> {form} overrides={overrides.clone()} />
+        }
+    }
+
+    fn render(overrides: fn() -> AutoFormOverrides) -> String {
+        futures::executor::block_on(
+            ServerRenderer::<SignupForm>::with_props(move || SignupFormProps {
+                overrides: overrides(),
+            })
+            .hydratable(false)
+            .render(),
+        )
+    }
+
+    /// The opening tags of the controls, in the order they are rendered
+    fn controls(html: &str) -> Vec<&str> {
+        html.match_indices('<')
+            .map(|(start, _)| &html[start..start + html[start..].find('>').unwrap() + 1])
+            .filter(|tag| {
+                ["<input", "<select"]
+                    .iter()
+                    .any(|name| tag.starts_with(name))
+            })
+            .collect()
+    }
+
+    fn control<'a>(controls: &[&'a str], id: &str) -> &'a str {
+        let id = format!("id=\"{}\"", id);
+
+        controls
+            .iter()
+            .find(|tag| tag.contains(&id))
+            .unwrap_or_else(|| panic!("no control with {}", id))
+    }
+
+    #[test]
+    fn test_control_for_kind() {
+        let html = render(AutoFormOverrides::new);
+        let controls = controls(&html);
+
+        assert!(control(&controls, "name").contains("type=\"text\""));
+        assert!(control(&controls, "newsletter").contains("type=\"checkbox\""));
+        assert!(control(&controls, "plan").starts_with("<select"));
+        assert!(control(&controls, "address.city").contains("type=\"text\""));
+        assert!(control(&controls, "avatar").contains("type=\"file\""));
+        assert!(html.contains("<fieldset name=\"address\"><legend>Address</legend>"));
+    }
+
+    #[test]
+    fn test_order_and_hide_groups() {
+        let html = render(|| {
+            AutoFormOverrides::new()
+                .order(["address", "plan"])
+                .hide("avatar")
+        });
+        let ids: Vec<&str> = controls(&html)
+            .into_iter()
+            .filter_map(|tag| tag.split("id=\"").nth(1)?.split('"').next())
+            .collect();
+
+        assert_eq!(ids, ["address.city", "plan", "name", "newsletter"]);
+
+        let html = render(|| AutoFormOverrides::new().hide("address"));
+        assert!(!html.contains("address"));
+    }
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!(humanize("first_name"), "First name");
+        assert_eq!(humanize("0"), "#1");
+        assert_eq!(humanize("$variant"), "Variant");
+        assert_eq!(humanize("$present"), "Present");
+    }
+}
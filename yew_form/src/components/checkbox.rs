@@ -1,4 +1,4 @@
-use crate::{Form, Model};
+use crate::{FieldName, Form, Model};
 use yew::prelude::*;
 
 pub enum CheckBoxMessage {
@@ -7,7 +7,7 @@ pub enum CheckBoxMessage {
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct CheckBoxProps<T: Model> {
-    pub field_name: AttrValue,
+    pub field_name: FieldName<T, bool>,
     pub form: Form<T>,
     #[prop_or_default]
     pub classes: Classes,
@@ -25,6 +25,7 @@ pub fn check_box<T: Model>(
     }: &CheckBoxProps<T>,
 ) -> Html {
     let value = *form.value(field_name) == "true";
+    let disabled = form.field(field_name).disabled();
 
     let ontoggle = {
         let form = form.clone();
@@ -41,9 +42,11 @@ pub fn check_box<T: Model>(
         <input
             class={classes.clone()}
             type="checkbox"
+            id={field_name}
             value={value.to_string()}
             onclick={ontoggle}
             checked={value}
+            {disabled}
             class={classes.clone()}
          />
     }
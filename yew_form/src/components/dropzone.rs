@@ -0,0 +1,417 @@
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+use web_sys::{ClipboardEvent, DragEvent, FileList, HtmlInputElement, Url};
+use yew::prelude::*;
+
+use crate::files::{FormFile, FormFiles};
+use crate::form::Form;
+use crate::{FieldName, Model};
+
+/// A file to upload, given to the `uploader` of a [`Dropzone`] when the file is added
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileUpload {
+    pub field_name: AttrValue,
+    pub file: FormFile,
+    /// Reports the progress of the upload, from `0.0` to `1.0`
+    pub progress: Callback<f64>,
+}
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct DropzoneProps<T: Model> {
+    pub form: Form<T>,
+    pub field_name: FieldName<T, FormFiles>,
+    #[prop_or_default]
+    pub disabled: bool,
+    /// Keeps the files already added when more are added, instead of replacing them
+    #[prop_or_default]
+    pub multiple: bool,
+    /// The accepted types, by default the `accept` of the [`FileRules`](crate::FileRules) of the field
+    #[prop_or_default]
+    pub accept: AttrValue,
+    /// By default the `dropzone_hint` of the [`MessageCatalog`](crate::messages::MessageCatalog) of the form
+    #[prop_or_default]
+    pub hint: Option<AttrValue>,
+    #[prop_or_default]
+    pub classes: Classes,
+    #[prop_or_else(|| "is-invalid".into())]
+    pub classes_invalid: Classes,
+    #[prop_or_else(|| "is-valid".into())]
+    pub classes_valid: Classes,
+    /// Classes that are applied while files are dragged over the dropzone
+    #[prop_or_else(|| "is-active".into())]
+    pub classes_active: Classes,
+    /// Uploads the files as they are added, their progress is shown until it reaches `1.0`
+    #[prop_or_default]
+    pub uploader: Option<Callback<FileUpload>>,
+}
+
+/// The uploads that didn't finish yet, by upload id, with their file and progress
+#[derive(Default, PartialEq)]
+struct Uploads(Vec<(u64, FormFile, f64)>);
+
+enum UploadAction {
+    Start(u64, FormFile),
+    Progress(u64, f64),
+    /// Drops the uploads of the files that were removed from the field
+    Retain(FormFiles),
+}
+
+impl Reducible for Uploads {
+    type Action = UploadAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut uploads = self.0.clone();
+
+        match action {
+            UploadAction::Start(id, file) => uploads.push((id, file, 0.0)),
+            UploadAction::Progress(id, progress) if progress >= 1.0 => {
+                uploads.retain(|(upload, ..)| *upload != id)
+            }
+            // The progress of an upload that was dropped is ignored
+            UploadAction::Progress(id, progress) => uploads
+                .iter_mut()
+                .filter(|(upload, ..)| *upload == id)
+                .for_each(|(.., value)| *value = progress.max(0.0)),
+            UploadAction::Retain(files) => {
+                uploads.retain(|(_, file, _)| files.iter().any(|f| f == file))
+            }
+        }
+
+        Rc::new(Uploads(uploads))
+    }
+}
+
+/// Gives the files the ids they had at the previous render and new ids to the added files,
+/// a file added twice gets an id per copy
+fn assign_ids(known: &mut Vec<(u64, FormFile)>, files: &FormFiles, next_id: &mut u64) -> Vec<u64> {
+    let mut previous = std::mem::take(known);
+
+    for file in files.iter() {
+        let id = match previous.iter().position(|(_, f)| f == file) {
+            Some(index) => previous.remove(index).0,
+            None => {
+                *next_id += 1;
+                *next_id
+            }
+        };
+
+        known.push((id, file.clone()));
+    }
+
+    known.iter().map(|(id, _)| *id).collect()
+}
+
+const DEFAULT_HINT: &str = "Drop files here, paste them or click to browse";
+
+/// An object URL showing an image file, revoked when it is dropped
+#[derive(PartialEq)]
+struct Thumbnail(String);
+
+impl Thumbnail {
+    fn new(file: &FormFile) -> Option<Self> {
+        file.mime_type()
+            .starts_with("image/")
+            .then(|| Url::create_object_url_with_blob(file.file()).ok())
+            .flatten()
+            .map(Thumbnail)
+    }
+}
+
+impl Drop for Thumbnail {
+    fn drop(&mut self) {
+        let _ = Url::revoke_object_url(&self.0);
+    }
+}
+
+#[function_component(Dropzone)]
+pub fn dropzone<T: Model>(
+    DropzoneProps {
+        form,
+        field_name,
+        disabled,
+        multiple,
+        accept,
+        hint,
+        classes,
+        classes_invalid,
+        classes_valid,
+        classes_active,
+        uploader,
+    }: &DropzoneProps<T>,
+) -> Html {
+    let files = form.files(field_name);
+    let field = form.field(field_name);
+    let disabled = *disabled || field.disabled();
+    let thumbnails = use_memo(
+        |files| files.iter().map(Thumbnail::new).collect::<Vec<_>>(),
+        files.clone(),
+    );
+    let uploads = use_reducer(Uploads::default);
+    // The ids of the uploads and of the files, which key the items of the files
+    let next_id = use_mut_ref(|| 0u64);
+    let known_files = use_mut_ref(Vec::new);
+    let ids = assign_ids(
+        &mut known_files.borrow_mut(),
+        &files,
+        &mut next_id.borrow_mut(),
+    );
+    let active = use_state_eq(|| false);
+    let hint = hint
+        .clone()
+        .unwrap_or_else(|| form.text("dropzone_hint", DEFAULT_HINT));
+
+    {
+        let uploads = uploads.dispatcher();
+
+        use_effect_with_deps(
+            move |files| {
+                uploads.dispatch(UploadAction::Retain(files.clone()));
+                || ()
+            },
+            files.clone(),
+        );
+    }
+    let accept = match accept.is_empty() {
+        true => form.file_rules(field_name).and_then(|rules| rules.accept),
+        false => Some(accept.clone()),
+    };
+    let classes = classes!(
+        classes.clone(),
+        field.dirty().then(|| match field.valid() {
+            true => classes_valid.clone(),
+            false => classes_invalid.clone(),
+        }),
+        (*active).then(|| classes_active.clone())
+    );
+
+    let add = {
+        let form = form.clone();
+        let field_name = field_name.clone();
+        let files = files.clone();
+        let multiple = *multiple;
+        let uploader = uploader.clone();
+        let uploads = uploads.dispatcher();
+        let next_id = next_id.clone();
+
+        Callback::from(move |list: FileList| {
+            let added = FormFiles::from(&list);
+
+            if disabled || added.is_empty() {
+                return;
+            }
+
+            if let Some(uploader) = &uploader {
+                for file in added.iter() {
+                    let id = {
+                        let mut next_id = next_id.borrow_mut();
+                        *next_id += 1;
+                        *next_id
+                    };
+                    let uploads = uploads.clone();
+
+                    uploads.dispatch(UploadAction::Start(id, file.clone()));
+                    uploader.emit(FileUpload {
+                        field_name: field_name.path().clone(),
+                        file: file.clone(),
+                        progress: Callback::from(move |value| {
+                            uploads.dispatch(UploadAction::Progress(id, value))
+                        }),
+                    });
+                }
+            }
+
+            let mut files = match multiple {
+                true => files.clone(),
+                false => FormFiles::default(),
+            };
+            added.iter().cloned().for_each(|file| files.push(file));
+            form.set_files(&field_name, files);
+        })
+    };
+
+    let ondragover = {
+        let active = active.clone();
+
+        Callback::from(move |e: DragEvent| {
+            // Allows the files to be dropped
+            e.prevent_default();
+            active.set(!disabled);
+        })
+    };
+
+    let ondragleave = {
+        let active = active.clone();
+
+        Callback::from(move |_: DragEvent| active.set(false))
+    };
+
+    let ondrop = {
+        let add = add.clone();
+        let active = active.clone();
+
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+            active.set(false);
+
+            if let Some(list) = e.data_transfer().and_then(|data| data.files()) {
+                add.emit(list);
+            }
+        })
+    };
+
+    let onpaste = {
+        let add = add.clone();
+
+        Callback::from(move |e: Event| {
+            if let Some(list) = e
+                .dyn_ref::<ClipboardEvent>()
+                .and_then(|e| e.clipboard_data())
+                .and_then(|data| data.files())
+                .filter(|list| list.length() > 0)
+            {
+                e.prevent_default();
+                add.emit(list);
+            }
+        })
+    };
+
+    let onchange = Callback::from(move |e: Event| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            if let Some(list) = input.files() {
+                add.emit(list);
+            }
+
+            // Selecting the same file again must trigger a change
+            input.set_value("");
+        }
+    });
+
+    let file_items = files
+        .iter()
+        .zip(thumbnails.iter())
+        .zip(ids)
+        .map(|((file, thumbnail), id)| {
+            let onremove = {
+                let form = form.clone();
+                let field_name = field_name.clone();
+                let known_files = known_files.clone();
+
+                // Removes the file with the id, wherever it moved since the render
+                Callback::from(move |_: MouseEvent| {
+                    let mut files = form.files(&field_name);
+                    let index = known_files
+                        .borrow()
+                        .iter()
+                        .find(|(known, _)| *known == id)
+                        .and_then(|(_, file)| files.iter().position(|f| f == file));
+
+                    if let Some(index) = index {
+                        files.remove(index);
+                        form.set_files(&field_name, files);
+                    }
+                })
+            };
+            // The latest upload of the file, if it was added more than once
+            let progress = uploads
+                .0
+                .iter()
+                .rev()
+                .find(|(_, upload, _)| upload == file)
+                .map(|(.., progress)| *progress);
+
+            html! {
+                <li class="dropzone-file" key={id}>
+                    if let Some(Thumbnail(url)) = thumbnail {
+                        <img class="dropzone-thumbnail" src={url.clone()} alt={file.name()} />
+                    }
+                    <span class="dropzone-name">{file.name()}</span>
+                    if let Some(progress) = progress {
+                        <progress class="dropzone-progress" max="1" value={progress.to_string()} />
+                    }
+                    <button type="button" class="dropzone-remove" onclick={onremove} {disabled}>
+                        {"×"}
+                    </button>
+                </li>
+            }
+        });
+
+    html! {
+        <div
+            class={classes}
+            tabindex="0"
+            {ondragover}
+            {ondragleave}
+            {ondrop}
+            {onpaste}
+        >
+            <input
+                id={field_name}
+                type="file"
+                name={field_name}
+                {accept}
+                {disabled}
+                multiple={*multiple}
+                hidden={true}
+                {onchange}
+            />
+            <label class="dropzone-hint" for={field_name}>{hint}</label>
+            <ul class="dropzone-files">
+                { for file_items }
+            </ul>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yew::prelude::*;
+    use yew::ServerRenderer;
+
+    use super::Dropzone;
+    use crate::fixtures::{profile, Profile};
+    use crate::messages::MessageMap;
+    use crate::use_form;
+
+    #[derive(Properties, PartialEq)]
+    struct UploadProps {
+        locale: AttrValue,
+    }
+
+    #[function_component(Upload)]
+    fn upload(UploadProps { locale }: &UploadProps) -> Html {
+        let form = use_form(profile);
+
+        use_memo(
+            |locale| {
+                form.set_messages(MessageMap::new().with(
+                    "fr",
+                    "dropzone_hint",
+                    "Déposez vos fichiers ici",
+                ));
+                form.set_locale(locale.clone());
+            },
+            locale.clone(),
+        );
+
+        html! {
+            <Dropzone<Profile> {form} field_name="avatar" />
+        }
+    }
+
+    fn render(locale: &'static str) -> String {
+        futures::executor::block_on(
+            ServerRenderer::<Upload>::with_props(move || UploadProps {
+                locale: locale.into(),
+            })
+            .hydratable(false)
+            .render(),
+        )
+    }
+
+    #[test]
+    fn test_hint() {
+        assert!(render("fr").contains(">Déposez vos fichiers ici</label>"));
+        assert!(render("en").contains(">Drop files here, paste them or click to browse</label>"));
+    }
+}
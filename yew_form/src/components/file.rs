@@ -1,5 +1,5 @@
 use crate::form::Form;
-use crate::Model;
+use crate::{FieldName, FormFiles, Model};
 use web_sys::HtmlInputElement;
 use web_sys::InputEvent;
 use yew::prelude::*;
@@ -11,7 +11,7 @@ pub enum FileMessage {
 #[derive(Properties, PartialEq, Clone)]
 pub struct FilePropeties<T: Model> {
     pub form: Form<T>,
-    pub field_name: AttrValue,
+    pub field_name: FieldName<T, FormFiles>,
     #[prop_or_default]
     pub disabled: bool,
     #[prop_or_default]
@@ -45,10 +45,27 @@ pub fn file<T: Model>(
         oninput,
     }: &FilePropeties<T>,
 ) -> Html {
+    let rules = form.file_rules(field_name);
+
+    // The files are checked against `accept` when the rules of the field don't set one
+    use_memo(
+        |(field_name, accept, rules)| {
+            if !accept.is_empty() && rules.as_ref().map_or(true, |r| r.accept.is_none()) {
+                let rules = rules.clone().unwrap_or_default();
+                form.set_file_rules(field_name, rules.with_accept(accept.clone()));
+            }
+        },
+        (field_name.clone(), accept.clone(), rules.clone()),
+    );
+
     let field = form.field(field_name);
+    let accept = match accept.is_empty() {
+        true => rules.and_then(|rules| rules.accept),
+        false => Some(accept.clone()),
+    };
     let classes = classes!(
         classes.clone(),
-        field.dirty().then(|| match field.valid {
+        field.dirty().then(|| match field.valid() {
             true => classes_valid.clone(),
             false => classes_invalid.clone(),
         })
@@ -58,8 +75,11 @@ pub fn file<T: Model>(
         let field_name = field_name.clone();
 
         move |e: InputEvent| {
-            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                form.set_value(&field_name, input.value());
+            if let Some(files) = e
+                .target_dyn_into::<HtmlInputElement>()
+                .and_then(|input| input.files())
+            {
+                form.set_files(&field_name, &files);
             }
 
             e
@@ -72,7 +92,7 @@ pub fn file<T: Model>(
             type="file"
             name={field_name}
             {accept}
-            disabled={*disabled}
+            disabled={*disabled || field.disabled()}
             multiple={*multiple}
             class={classes}
             {oninput}
@@ -80,3 +100,65 @@ pub fn file<T: Model>(
         />
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use yew::prelude::*;
+    use yew::LocalServerRenderer;
+
+    use super::File;
+    use crate::files::{FileRules, FormFile};
+    use crate::fixtures::{profile, Profile};
+    use crate::{use_form, Form};
+
+    #[derive(Properties, PartialEq)]
+    struct AvatarProps {
+        accept: AttrValue,
+        onform: Callback<Form<Profile>>,
+    }
+
+    #[function_component(Avatar)]
+    fn avatar(AvatarProps { accept, onform }: &AvatarProps) -> Html {
+        let form = use_form(profile);
+        use_memo(|_| onform.emit(form.clone()), ());
+
+        html! {
+            <File<Profile> {form} field_name="avatar" accept={accept.clone()} />
+        }
+    }
+
+    #[test]
+    fn test_accept() {
+        let form = Rc::new(RefCell::new(None));
+        let onform = {
+            let form = form.clone();
+            Callback::from(move |f| *form.borrow_mut() = Some(f))
+        };
+        let html = futures::executor::block_on(
+            LocalServerRenderer::<Avatar>::with_props(AvatarProps {
+                accept: "image/*".into(),
+                onform,
+            })
+            .hydratable(false)
+            .render(),
+        );
+        assert!(html.contains(r#"accept="image/*""#));
+
+        let form = form.borrow_mut().take().unwrap();
+        assert_eq!(
+            form.file_rules("avatar"),
+            Some(FileRules::new().with_accept("image/*"))
+        );
+
+        form.set_files(
+            "avatar",
+            vec![FormFile::fake("notes.txt", "text/plain", 12)],
+        );
+        let field = form.field("avatar");
+        assert!(!field.valid());
+        assert_eq!(field.message(), "notes.txt is not an accepted type of file");
+    }
+}
@@ -8,8 +8,10 @@ use yew::prelude::*;
 #[cfg(feature = "ybc")]
 use ybc;
 
+use crate::components::Constraints;
 use crate::form::Form;
-use crate::Model;
+use crate::model::ValueKind;
+use crate::{FieldName, Model};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
@@ -19,11 +21,34 @@ pub enum InputType {
     Email,
     Tel,
     Url,
+    Number,
     Date,
+    Time,
+    #[strum(serialize = "datetime-local")]
+    DatetimeLocal,
+    Month,
+    Week,
 }
 
 impl ImplicitClone for InputType {}
 
+impl InputType {
+    /// The type of input used for values of `kind`
+    pub fn for_kind(kind: ValueKind) -> Self {
+        match kind {
+            ValueKind::Integer | ValueKind::Number => InputType::Number,
+            ValueKind::Date => InputType::Date,
+            ValueKind::Time => InputType::Time,
+            ValueKind::DateTime => InputType::DatetimeLocal,
+            ValueKind::Month => InputType::Month,
+            ValueKind::Week => InputType::Week,
+            ValueKind::Text | ValueKind::Bool | ValueKind::Options | ValueKind::Files => {
+                InputType::Text
+            }
+        }
+    }
+}
+
 impl IntoPropValue<Option<AttrValue>> for InputType {
     fn into_prop_value(self) -> Option<AttrValue> {
         <AttrValue as From<&'static str>>::from(self.into()).into()
@@ -47,9 +72,10 @@ impl InputType {
 pub struct InputProps<T: Model> {
     #[prop_or_default]
     pub autocomplete: bool,
-    #[prop_or(InputType::Text)]
-    pub input_type: InputType,
-    pub field_name: AttrValue,
+    /// The type of input, by default it follows the kind of the field's value
+    #[prop_or_default]
+    pub input_type: Option<InputType>,
+    pub field_name: FieldName<T>,
     pub form: Form<T>,
     #[prop_or_default]
     pub placeholder: AttrValue,
@@ -83,9 +109,33 @@ pub fn input<T: Model>(
     }: &InputProps<T>,
 ) -> Html {
     let field = form.field(field_name);
+    let meta = T::field_meta(field_name.as_ref());
+    let kind = meta.as_ref().map(|meta| meta.kind);
+    // Numbers shown in a number format of the form don't fit in a number input
+    let formatted = form.number_format(field_name).is_some();
+    let input_type = match (*input_type, kind) {
+        (Some(input_type), _) => input_type,
+        (None, Some(kind)) if !formatted => InputType::for_kind(kind),
+        (None, _) => InputType::Text,
+    };
+    let inputmode = match kind {
+        Some(ValueKind::Integer) if formatted => Some("numeric"),
+        Some(ValueKind::Number) if formatted => Some("decimal"),
+        _ => None,
+    };
+    let step =
+        (input_type == InputType::Number && kind == Some(ValueKind::Number)).then_some("any");
+    let Constraints {
+        required,
+        min,
+        max,
+        minlength,
+        maxlength,
+        pattern,
+    } = meta.as_ref().map(Constraints::from).unwrap_or_default();
     let classes = classes!(
         classes.clone(),
-        field.dirty().then(|| match field.valid {
+        field.dirty().then(|| match field.valid() {
             true => classes_valid.clone(),
             false => classes_invalid.clone(),
         })
@@ -115,7 +165,7 @@ pub fn input<T: Model>(
                 {placeholder}
                 value={&field.value}
                 update={oninput}
-                disabled={*disabled}
+                disabled={*disabled || field.disabled()}
             />
         };
     }
@@ -130,12 +180,20 @@ pub fn input<T: Model>(
         <input
             id={field_name}
             class={classes}
-            type={*input_type}
+            type={input_type}
             {autocomplete}
             {placeholder}
+            {inputmode}
+            {required}
+            {min}
+            {max}
+            {step}
+            {minlength}
+            {maxlength}
+            {pattern}
             value={&field.value}
             {oninput}
-            disabled={*disabled}
+            disabled={*disabled || field.disabled()}
         />
     }
 }
@@ -1,11 +1,53 @@
+pub mod auto_form;
 pub mod checkbox;
+pub mod dropzone;
 pub mod file;
 pub mod input;
 pub mod select;
 pub mod textarea;
 
+pub use auto_form::{AutoForm, AutoFormOverrides};
 pub use checkbox::CheckBox;
+pub use dropzone::{Dropzone, FileUpload};
 pub use file::File;
 pub use input::{Input, InputType};
 pub use select::{Select, SelectOption};
 pub use textarea::TextArea;
+
+use yew::AttrValue;
+
+use crate::{FieldMeta, Model};
+
+/// The HTML attributes constraining the input of a field, derived from its [`FieldMeta`]
+#[derive(Default)]
+pub(crate) struct Constraints {
+    pub required: bool,
+    pub min: Option<AttrValue>,
+    pub max: Option<AttrValue>,
+    pub minlength: Option<AttrValue>,
+    pub maxlength: Option<AttrValue>,
+    pub pattern: Option<AttrValue>,
+}
+
+impl Constraints {
+    pub fn new<T: Model>(field_name: &str) -> Self {
+        T::field_meta(field_name)
+            .map(|meta| Constraints::from(&meta))
+            .unwrap_or_default()
+    }
+}
+
+impl From<&FieldMeta> for Constraints {
+    fn from(meta: &FieldMeta) -> Self {
+        let attr = |value: Option<String>| value.map(AttrValue::from);
+
+        Constraints {
+            required: meta.required,
+            min: attr(meta.min.map(|min| min.to_string())),
+            max: attr(meta.max.map(|max| max.to_string())),
+            minlength: attr(meta.min_length.map(|min| min.to_string())),
+            maxlength: attr(meta.max_length.map(|max| max.to_string())),
+            pattern: meta.pattern.clone(),
+        }
+    }
+}
@@ -10,8 +10,9 @@ use yew::virtual_dom::VText;
 #[cfg(feature = "ybc")]
 use ybc;
 
+use crate::components::Constraints;
 use crate::form::Form;
-use crate::Model;
+use crate::{FieldName, Model};
 
 pub enum SelectMessage {
     OnInput(InputEvent),
@@ -35,11 +36,11 @@ impl From<Html> for Options {
     }
 }
 
-impl Into<Html> for Options {
-    fn into(self) -> Html {
-        match self {
+impl From<Options> for Html {
+    fn from(options: Options) -> Self {
+        match options {
             Options::Controlled(child) => child.into(),
-            Options::Uncontrolled(child) => child.into(),
+            Options::Uncontrolled(child) => child,
         }
     }
 }
@@ -47,7 +48,7 @@ impl Into<Html> for Options {
 #[derive(Properties, PartialEq, Clone)]
 pub struct SelectProps<T: Model> {
     pub form: Form<T>,
-    pub field_name: AttrValue,
+    pub field_name: FieldName<T>,
     pub children: ChildrenRenderer<Options>,
     #[prop_or_default]
     pub autocomplete: bool,
@@ -80,11 +81,12 @@ pub fn select<T: Model>(
         onchange,
     }: &SelectProps<T>,
 ) -> Html {
-    let field = form.field(&field_name);
+    let field = form.field(field_name);
     let selected = &field.value;
+    let required = Constraints::new::<T>(field_name.as_ref()).required;
     let classes = classes!(
         classes.clone(),
-        field.dirty().then(|| match field.valid {
+        field.dirty().then(|| match field.valid() {
             true => classes_valid.clone(),
             false => classes_invalid.clone(),
         })
@@ -108,12 +110,12 @@ pub fn select<T: Model>(
        <ybc::Select
             name={field_name}
             {classes}
-            disabled={*disabled}
+            disabled={*disabled || field.disabled()}
             update={onchange}>
             { for children.iter().map(|option| {
                 match option {
                     Options::Controlled(mut option) => {
-                        let mut props = Rc::make_mut(&mut option.props);
+                        let props = Rc::make_mut(&mut option.props);
                         props.selected = props.value == *selected;
                         option.into()
                     },
@@ -131,7 +133,8 @@ pub fn select<T: Model>(
             id={field_name}
             name={field_name}
             autocomplete={if *autocomplete {"on"} else {"off"}}
-            disabled={*disabled}
+            disabled={*disabled || field.disabled()}
+            {required}
             multiple={*multiple}
             class={classes}
             {onchange}
@@ -139,7 +142,7 @@ pub fn select<T: Model>(
             { for children.iter().map(|option| {
                 match option {
                     Options::Controlled(mut option) => {
-                        let mut props = Rc::make_mut(&mut option.props);
+                        let props = Rc::make_mut(&mut option.props);
                         props.selected = props.value == *selected;
                         option.into()
                     },
@@ -5,8 +5,9 @@ use yew::html::ImplicitClone;
 use yew::html::IntoPropValue;
 use yew::prelude::*;
 
+use crate::components::Constraints;
 use crate::form::Form;
-use crate::Model;
+use crate::{FieldName, Model};
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Wrap {
@@ -28,7 +29,7 @@ impl IntoPropValue<Option<AttrValue>> for &Wrap {
 #[derive(Properties, PartialEq, Clone)]
 pub struct TextAreaProps<T: Model> {
     pub form: Form<T>,
-    pub field_name: AttrValue,
+    pub field_name: FieldName<T>,
     pub oninput: Callback<InputEvent>,
     #[prop_or_default]
     pub classes: Classes,
@@ -74,9 +75,15 @@ pub fn text_area<T: Model>(
     }: &TextAreaProps<T>,
 ) -> Html {
     let field = form.field(field_name);
+    let Constraints {
+        required,
+        minlength,
+        maxlength,
+        ..
+    } = Constraints::new::<T>(field_name.as_ref());
     let classes = classes!(
         classes.clone(),
-        field.dirty().then(|| match field.valid {
+        field.dirty().then(|| match field.valid() {
             true => classes_valid.clone(),
             false => classes_invalid.clone(),
         })
@@ -106,8 +113,11 @@ pub fn text_area<T: Model>(
             spellcheck={spellcheck.to_string()}
             autocomplete={autocomplete.to_string()}
             autocorrect={autocorrect.to_string()}
+            {required}
+            {minlength}
+            {maxlength}
             {oninput}
-            disabled={*disabled}
+            disabled={*disabled || field.disabled()}
         />
     }
 }
@@ -0,0 +1,389 @@
+//! [`FormValue`] implementations for `chrono` and `time` types, behind the features of the same name.
+//!
+//! Values are formatted the way `<input>` elements of the matching [`InputType`](crate::InputType) expect them:
+//! - `date`: `2022-12-31`
+//! - `time`: `13:45`, with seconds and milliseconds when they are not zero
+//! - `datetime-local`: `2022-12-31T13:45`
+//! - `month`: `2022-12`, see [`Month`]
+//! - `week`: `2022-W52`, see [`Week`]
+//!
+//! [`OffsetDateTime`](time::OffsetDateTime) is edited with `datetime-local` in its own offset.
+
+use std::ops::{Deref, DerefMut};
+
+use yew::AttrValue;
+
+use crate::error::ConversionError;
+use crate::model::{FormValue, ValueKind};
+
+/// A date edited with `<input type="month">`, set to the first day of the month
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Month<T>(pub T);
+
+/// A date edited with `<input type="week">`, set to the monday of the ISO week
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Week<T>(pub T);
+
+impl<T> Deref for Month<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Month<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> Deref for Week<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Week<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+fn date_error<T>(input: &str, code: &'static str) -> ConversionError {
+    let code = if input.is_empty() { "empty" } else { code };
+
+    ConversionError::new(std::any::type_name::<T>(), input.to_owned(), code)
+}
+
+/// Parses `2022-12`
+fn parse_month(value: &str) -> Option<(i32, u8)> {
+    let (year, month) = value.split_once('-')?;
+
+    Some((year.parse().ok()?, month.parse().ok()?))
+}
+
+/// Parses `2022-W52`
+fn parse_week(value: &str) -> Option<(i32, u8)> {
+    let (year, week) = value.split_once("-W")?;
+
+    Some((year.parse().ok()?, week.parse().ok()?))
+}
+
+/// Implements [`FormValue`] by trying `parse` and formatting with `format`,
+/// sub-forms start with the value `new`
+macro_rules! impl_date_value {
+    ($t:ty, $kind:ident, $code:literal, |$v:ident| $format:expr, |$s:ident| $parse:expr, new = $new:expr) => {
+        impl FormValue for $t {
+            fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+                let $v = self;
+                field_path.is_empty().then(|| AttrValue::from($format))
+            }
+
+            fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+                if !field_path.is_empty() {
+                    return Err(ConversionError::unknown_field::<Self>(field_path, value));
+                }
+
+                *self = Self::parse_value(value)?;
+                Ok(())
+            }
+
+            fn kind(field_path: &str) -> Option<ValueKind> {
+                field_path.is_empty().then_some(ValueKind::$kind)
+            }
+
+            fn parse_value(value: &str) -> Result<Self, ConversionError> {
+                let $s = value;
+                $parse.ok_or_else(|| date_error::<$t>(value, $code))
+            }
+
+            fn new_value() -> Option<Self> {
+                Some($new)
+            }
+        }
+
+        crate::paths::impl_scalar_paths!($t);
+    };
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_values {
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+    use super::*;
+
+    fn format_time(time: &NaiveTime) -> String {
+        match (time.second(), time.nanosecond()) {
+            (0, 0) => time.format("%H:%M").to_string(),
+            (_, 0) => time.format("%H:%M:%S").to_string(),
+            _ => time.format("%H:%M:%S%.3f").to_string(),
+        }
+    }
+
+    impl_date_value!(
+        NaiveDate,
+        Date,
+        "invalid_date",
+        |v| v.format("%Y-%m-%d").to_string(),
+        |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok(),
+        new = Default::default()
+    );
+
+    impl_date_value!(
+        NaiveTime,
+        Time,
+        "invalid_time",
+        |v| format_time(v),
+        |s| NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+            .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+            .ok(),
+        new = Default::default()
+    );
+
+    impl_date_value!(
+        NaiveDateTime,
+        DateTime,
+        "invalid_datetime",
+        |v| format!("{}T{}", v.date().format("%Y-%m-%d"), format_time(&v.time())),
+        |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M"))
+            .ok(),
+        new = Default::default()
+    );
+
+    impl_date_value!(
+        Month<NaiveDate>,
+        Month,
+        "invalid_date",
+        |v| v.0.format("%Y-%m").to_string(),
+        |s| parse_month(s)
+            .and_then(|(year, month)| NaiveDate::from_ymd_opt(year, month.into(), 1))
+            .map(Month),
+        new = Default::default()
+    );
+
+    impl_date_value!(
+        Week<NaiveDate>,
+        Week,
+        "invalid_date",
+        |v| format!("{:04}-W{:02}", v.0.iso_week().year(), v.0.iso_week().week()),
+        |s| parse_week(s)
+            .and_then(|(year, week)| NaiveDate::from_isoywd_opt(year, week.into(), Weekday::Mon))
+            .map(Week),
+        new = Default::default()
+    );
+}
+
+#[cfg(feature = "time")]
+mod time_values {
+    use time::format_description::FormatItem;
+    use time::macros::format_description;
+    use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, Weekday};
+
+    use super::*;
+
+    /// The value sub-forms start with
+    const EPOCH: OffsetDateTime = OffsetDateTime::UNIX_EPOCH;
+    const DATE: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]");
+    const MONTH: &[FormatItem<'_>] = format_description!("[year]-[month]");
+    const TIME_FORMATS: [&[FormatItem<'_>]; 3] = [
+        format_description!("[hour]:[minute]:[second].[subsecond]"),
+        format_description!("[hour]:[minute]:[second]"),
+        format_description!("[hour]:[minute]"),
+    ];
+
+    fn format_time(time: &Time) -> String {
+        let format = match (time.second(), time.millisecond()) {
+            (0, 0) => format_description!("[hour]:[minute]"),
+            (_, 0) => format_description!("[hour]:[minute]:[second]"),
+            _ => format_description!("[hour]:[minute]:[second].[subsecond digits:3]"),
+        };
+
+        time.format(format).unwrap_or_default()
+    }
+
+    fn parse_time(value: &str) -> Option<Time> {
+        TIME_FORMATS
+            .iter()
+            .find_map(|format| Time::parse(value, format).ok())
+    }
+
+    fn format_datetime(date: Date, time: Time) -> String {
+        format!(
+            "{}T{}",
+            date.format(DATE).unwrap_or_default(),
+            format_time(&time)
+        )
+    }
+
+    fn parse_datetime(value: &str) -> Option<PrimitiveDateTime> {
+        let (date, time) = value.split_once('T')?;
+
+        Some(PrimitiveDateTime::new(
+            Date::parse(date, DATE).ok()?,
+            parse_time(time)?,
+        ))
+    }
+
+    impl_date_value!(
+        Date,
+        Date,
+        "invalid_date",
+        |v| v.format(DATE).unwrap_or_default(),
+        |s| Date::parse(s, DATE).ok(),
+        new = EPOCH.date()
+    );
+
+    impl_date_value!(
+        Time,
+        Time,
+        "invalid_time",
+        |v| format_time(v),
+        |s| parse_time(s),
+        new = Time::MIDNIGHT
+    );
+
+    impl_date_value!(
+        PrimitiveDateTime,
+        DateTime,
+        "invalid_datetime",
+        |v| format_datetime(v.date(), v.time()),
+        |s| parse_datetime(s),
+        new = PrimitiveDateTime::new(EPOCH.date(), EPOCH.time())
+    );
+
+    // Only the local date and time are edited, the offset of the value is kept
+    impl FormValue for OffsetDateTime {
+        fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+            field_path
+                .is_empty()
+                .then(|| format_datetime(self.date(), self.time()).into())
+        }
+
+        fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+            if !field_path.is_empty() {
+                return Err(ConversionError::unknown_field::<Self>(field_path, value));
+            }
+
+            match parse_datetime(value) {
+                Some(v) => {
+                    *self = v.assume_offset(self.offset());
+                    Ok(())
+                }
+                None => Err(date_error::<OffsetDateTime>(value, "invalid_datetime")),
+            }
+        }
+
+        fn kind(field_path: &str) -> Option<ValueKind> {
+            field_path.is_empty().then_some(ValueKind::DateTime)
+        }
+
+        fn parse_value(value: &str) -> Result<Self, ConversionError> {
+            parse_datetime(value)
+                .map(PrimitiveDateTime::assume_utc)
+                .ok_or_else(|| date_error::<OffsetDateTime>(value, "invalid_datetime"))
+        }
+
+        fn new_value() -> Option<Self> {
+            Some(EPOCH)
+        }
+    }
+
+    crate::paths::impl_scalar_paths!(OffsetDateTime);
+
+    impl_date_value!(
+        Month<Date>,
+        Month,
+        "invalid_date",
+        |v| v.0.format(MONTH).unwrap_or_default(),
+        |s| parse_month(s)
+            .and_then(|(year, month)| {
+                Date::from_calendar_date(year, month.try_into().ok()?, 1).ok()
+            })
+            .map(Month),
+        new = Month(EPOCH.date())
+    );
+
+    impl_date_value!(
+        Week<Date>,
+        Week,
+        "invalid_date",
+        |v| {
+            let (year, week, _) = v.0.to_iso_week_date();
+            format!("{:04}-W{:02}", year, week)
+        },
+        |s| parse_week(s)
+            .and_then(|(year, week)| Date::from_iso_week_date(year, week, Weekday::Monday).ok())
+            .map(Week),
+        new = Week(EPOCH.date())
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_values() {
+        use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+        let mut date = NaiveDate::default();
+        date.set_value("", "2022-12-31").unwrap();
+        assert_eq!(date.value(""), "2022-12-31");
+        assert_eq!(
+            date.set_value("", "31/12/2022").unwrap_err().code,
+            "invalid_date"
+        );
+
+        let mut time = NaiveTime::default();
+        time.set_value("", "13:45").unwrap();
+        assert_eq!(time.value(""), "13:45");
+        time.set_value("", "13:45:10.5").unwrap();
+        assert_eq!(time.value(""), "13:45:10.500");
+
+        let mut datetime = NaiveDateTime::default();
+        datetime.set_value("", "2022-12-31T13:45").unwrap();
+        assert_eq!(datetime.value(""), "2022-12-31T13:45");
+
+        let mut month = Month(date);
+        month.set_value("", "2023-02").unwrap();
+        assert_eq!(*month, NaiveDate::from_ymd_opt(2023, 2, 1).unwrap());
+
+        let mut week = Week(date);
+        assert_eq!(week.value(""), "2022-W52");
+        week.set_value("", "2023-W01").unwrap();
+        assert_eq!(*week, NaiveDate::from_ymd_opt(2023, 1, 2).unwrap());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_values() {
+        use time::macros::{date, datetime, offset};
+        use time::{OffsetDateTime, Time};
+
+        let mut d = date!(2022 - 01 - 01);
+        d.set_value("", "2022-12-31").unwrap();
+        assert_eq!(d.value(""), "2022-12-31");
+
+        let mut t = Time::MIDNIGHT;
+        t.set_value("", "08:05:30").unwrap();
+        assert_eq!(t.value(""), "08:05:30");
+
+        let mut dt: OffsetDateTime = datetime!(2022-01-01 0:00 +2);
+        dt.set_value("", "2022-12-31T13:45").unwrap();
+        assert_eq!(dt, datetime!(2022-12-31 13:45 +2));
+        assert_eq!(dt.offset(), offset!(+2));
+        assert_eq!(dt.value(""), "2022-12-31T13:45");
+
+        let mut week = Week(d);
+        week.set_value("", "2023-W01").unwrap();
+        assert_eq!(week.value(""), "2023-W01");
+        assert_eq!(*week, date!(2023 - 01 - 02));
+
+        assert_eq!(Month(d).value(""), "2022-12");
+    }
+}
@@ -0,0 +1,339 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
+use std::str::ParseBoolError;
+
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+use yew::AttrValue;
+
+use crate::model::join_field_path;
+
+/// Returned by [`FormValue::set_value`](crate::model::FormValue::set_value) when the
+/// input of a field can't be converted to the type of the field.
+///
+/// The `code` identifies the kind of failure, the built-in values use:
+/// - `empty`: the input is empty
+/// - `invalid_integer`: the input is not a whole number
+/// - `invalid_number`: the input is not a number
+/// - `invalid_bool`: the input is not `true` or `false`
+/// - `out_of_range`: the number doesn't fit in the type of the field
+/// - `unknown_variant`: the input is not one of the options of an enum
+/// - `unknown_field`: there is no field at the path, see [`FormError::UnknownField`]
+/// - `invalid_date`, `invalid_time`, `invalid_datetime`: the input is not a date or time, see [`dates`](crate::dates)
+/// - `invalid`: any other failure
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionError {
+    /// The path of the field that failed, relative to the value `set_value` was called on
+    pub path: AttrValue,
+    /// The name of the type the input had to be converted to
+    pub expected: &'static str,
+    /// The input that was rejected
+    pub input: AttrValue,
+    pub code: Cow<'static, str>,
+}
+
+impl ConversionError {
+    pub fn new(
+        expected: &'static str,
+        input: impl Into<AttrValue>,
+        code: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        ConversionError {
+            path: Default::default(),
+            expected,
+            input: input.into(),
+            code: code.into(),
+        }
+    }
+
+    /// Creates an error for an input that can't be converted to `T`,
+    /// deriving the code from the [`FromStr`](std::str::FromStr) error.
+    pub fn parse<T, E: ParseErrorCode>(input: &str, error: Option<&E>) -> Self {
+        let code = match error {
+            _ if input.is_empty() => "empty",
+            Some(error) => error.code(),
+            None => "invalid",
+        };
+
+        ConversionError::new(std::any::type_name::<T>(), input.to_owned(), code)
+    }
+
+    /// Creates an error for an input that can't be converted to `T`, for errors without a more specific code
+    pub fn invalid<T>(input: &str) -> Self {
+        ConversionError::parse::<T, Infallible>(input, None)
+    }
+
+    /// Creates an error for a path that doesn't exist in `T`
+    pub fn unknown_field<T: ?Sized>(field_path: &str, input: &str) -> Self {
+        ConversionError {
+            path: field_path.to_owned().into(),
+            ..ConversionError::new(
+                std::any::type_name::<T>(),
+                input.to_owned(),
+                "unknown_field",
+            )
+        }
+    }
+
+    /// Prepends `field_name` to the path, used while the error bubbles up to the model
+    pub fn prefixed(mut self, field_name: &str) -> Self {
+        self.path = join_field_path(field_name, &self.path).into();
+        self
+    }
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code.as_ref() {
+            "empty" => write!(f, "A value is required"),
+            "invalid_integer" => write!(f, "Enter a whole number"),
+            "invalid_number" => write!(f, "Enter a number"),
+            "invalid_bool" => write!(f, "Enter true or false"),
+            "out_of_range" => write!(f, "Value is out of range for {}", self.expected),
+            "unknown_variant" => write!(f, "Select one of the options"),
+            "invalid_date" => write!(f, "Enter a valid date"),
+            "invalid_time" => write!(f, "Enter a valid time"),
+            "invalid_datetime" => write!(f, "Enter a valid date and time"),
+            "unknown_field" => write!(f, "Field {} does not exist", self.path),
+            _ => write!(f, "Could not convert"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Returned by the `try_*` methods of [`Form`](crate::Form) instead of panicking
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormError {
+    /// There is no field at the path
+    UnknownField(AttrValue),
+    /// The input can't be converted, the field keeps the input and is marked invalid
+    Conversion(ConversionError),
+    /// The field is disabled, so its value can't be changed
+    Disabled(AttrValue),
+    /// The value at the path is not what the method works on, e.g. not a list,
+    /// or the value given for a row doesn't have the type of the rows
+    WrongType {
+        path: AttrValue,
+        /// What the value should be, e.g. `a list of u32`
+        expected: Cow<'static, str>,
+    },
+    /// The type has no [`new_value`](crate::model::FormValue::new_value) to start from,
+    /// e.g. when decoding with [`Model::from_urlencoded`](crate::Model::from_urlencoded)
+    NoNewValue(&'static str),
+    /// There is no row at the index in the list at the path
+    OutOfBounds {
+        path: AttrValue,
+        index: usize,
+        /// The number of rows of the list
+        len: usize,
+    },
+    /// There is no entry under the key in the map at the path
+    UnknownKey { path: AttrValue, key: AttrValue },
+    /// There already is an entry under the key in the map at the path
+    DuplicateKey { path: AttrValue, key: AttrValue },
+}
+
+impl FormError {
+    pub(crate) fn wrong_type(path: &str, expected: impl Into<Cow<'static, str>>) -> Self {
+        FormError::WrongType {
+            path: path.to_owned().into(),
+            expected: expected.into(),
+        }
+    }
+}
+
+impl From<ConversionError> for FormError {
+    fn from(error: ConversionError) -> Self {
+        match error.code.as_ref() {
+            "unknown_field" => FormError::UnknownField(error.path),
+            _ => FormError::Conversion(error),
+        }
+    }
+}
+
+impl Display for FormError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormError::UnknownField(path) => write!(f, "Field {} does not exist", path),
+            FormError::Conversion(error) => write!(f, "Field {}: {}", error.path, error),
+            FormError::Disabled(path) => write!(f, "Field {} is disabled", path),
+            FormError::WrongType { path, expected } => {
+                write!(f, "Field {} is not {}", path, expected)
+            }
+            FormError::NoNewValue(type_name) => write!(f, "{} has no new value", type_name),
+            FormError::OutOfBounds { path, index, len } => {
+                write!(
+                    f,
+                    "Row {} does not exist in {}, which has {} rows",
+                    index, path, len
+                )
+            }
+            FormError::UnknownKey { path, key } => {
+                write!(f, "Key {} does not exist in {}", key, path)
+            }
+            FormError::DuplicateKey { path, key } => {
+                write!(f, "Key {} already exists in {}", key, path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormError {}
+
+/// Validation errors found outside the form, e.g. by the server, see [`Form::set_errors`](crate::Form::set_errors).
+///
+/// They are created from [`ValidationErrors`], or from a map of field paths to messages,
+/// e.g. `{"address.city": ["Unknown city"]}`. The errors created from messages have the code `external`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExternalErrors(pub(crate) Vec<(AttrValue, ValidationError)>);
+
+impl ExternalErrors {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds an error with `message` for the field at `path`
+    pub fn with(mut self, path: impl Into<AttrValue>, message: impl Into<String>) -> Self {
+        self.0.push((path.into(), external_error(message.into())));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The errors with the path of their field
+    pub fn iter(&self) -> impl Iterator<Item = (&AttrValue, &ValidationError)> {
+        self.0.iter().map(|(path, error)| (path, error))
+    }
+
+    fn add(&mut self, prefix: &str, errors: &ValidationErrors) {
+        for (field_name, error) in errors.errors() {
+            let path = join_field_path(prefix, field_name);
+
+            match error {
+                ValidationErrorsKind::Struct(errors) => self.add(&path, errors),
+                ValidationErrorsKind::List(errors) => {
+                    for (index, errors) in errors {
+                        self.add(&join_field_path(&path, &index.to_string()), errors);
+                    }
+                }
+                ValidationErrorsKind::Field(errors) => self.0.extend(
+                    errors
+                        .iter()
+                        .map(|error| (AttrValue::from(path.clone()), error.clone())),
+                ),
+            }
+        }
+    }
+}
+
+fn external_error(message: String) -> ValidationError {
+    ValidationError {
+        message: Some(Cow::Owned(message)),
+        ..ValidationError::new("external")
+    }
+}
+
+impl From<&ValidationErrors> for ExternalErrors {
+    fn from(errors: &ValidationErrors) -> Self {
+        let mut external = ExternalErrors::new();
+        external.add("", errors);
+        external
+    }
+}
+
+impl From<ValidationErrors> for ExternalErrors {
+    fn from(errors: ValidationErrors) -> Self {
+        ExternalErrors::from(&errors)
+    }
+}
+
+impl<K, M, S> FromIterator<(K, M)> for ExternalErrors
+where
+    K: Into<AttrValue>,
+    M: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, M)>>(iter: I) -> Self {
+        ExternalErrors(
+            iter.into_iter()
+                .flat_map(|(path, messages)| {
+                    let path = path.into();
+
+                    messages
+                        .into_iter()
+                        .map(move |message| (path.clone(), external_error(message.into())))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl From<HashMap<String, Vec<String>>> for ExternalErrors {
+    fn from(errors: HashMap<String, Vec<String>>) -> Self {
+        errors.into_iter().collect()
+    }
+}
+
+impl From<BTreeMap<String, Vec<String>>> for ExternalErrors {
+    fn from(errors: BTreeMap<String, Vec<String>>) -> Self {
+        errors.into_iter().collect()
+    }
+}
+
+/// Maps a [`FromStr`](std::str::FromStr) error to the code of a [`ConversionError`]
+pub trait ParseErrorCode {
+    fn code(&self) -> &'static str {
+        "invalid"
+    }
+}
+
+impl ParseErrorCode for ParseIntError {
+    fn code(&self) -> &'static str {
+        match self.kind() {
+            IntErrorKind::Empty => "empty",
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => "out_of_range",
+            _ => "invalid_integer",
+        }
+    }
+}
+
+impl ParseErrorCode for ParseFloatError {
+    fn code(&self) -> &'static str {
+        "invalid_number"
+    }
+}
+
+impl ParseErrorCode for ParseBoolError {
+    fn code(&self) -> &'static str {
+        "invalid_bool"
+    }
+}
+
+impl ParseErrorCode for Infallible {}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::FormValue;
+
+    #[test]
+    fn test_conversion_error_code() {
+        let mut list = vec![0u32];
+
+        let error = list.set_value("0", "12.5").unwrap_err();
+        assert_eq!(error.path, "0");
+        assert_eq!(error.expected, "u32");
+        assert_eq!(error.input, "12.5");
+        assert_eq!(error.code, "invalid_integer");
+
+        let error = list.set_value("0", "99999999999").unwrap_err();
+        assert_eq!(error.code, "out_of_range");
+        assert_eq!(error.to_string(), "Value is out of range for u32");
+
+        assert_eq!(list.set_value("0", "").unwrap_err().code, "empty");
+    }
+}
@@ -0,0 +1,470 @@
+//! Files selected in a file input, and `multipart/form-data` bodies to upload them.
+//!
+//! A field of type [`FormFiles`] is bound with the [`File`](crate::File) component,
+//! which keeps the selected files in the model. [`Form::to_multipart`](crate::Form::to_multipart)
+//! then builds a body with the files and the other fields of the model.
+//!
+//! The files of a field are checked against the [`FileRules`] set with
+//! [`Form::set_file_rules`](crate::Form::set_file_rules), along with the validation of the model.
+
+use std::borrow::Cow;
+
+use validator::ValidationError;
+use wasm_bindgen::JsValue;
+use web_sys::{FileList, FormData};
+use yew::AttrValue;
+
+use crate::error::ConversionError;
+use crate::model::{matches_field_pattern, FormValue, ValueKind};
+
+/// A file selected in a file input
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormFile {
+    name: AttrValue,
+    size: u64,
+    mime_type: AttrValue,
+    file: web_sys::File,
+}
+
+impl FormFile {
+    pub fn name(&self) -> &AttrValue {
+        &self.name
+    }
+
+    /// The size in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The MIME type guessed by the browser, empty if it is unknown
+    pub fn mime_type(&self) -> &AttrValue {
+        &self.mime_type
+    }
+
+    /// The handle to read the content of the file
+    pub fn file(&self) -> &web_sys::File {
+        &self.file
+    }
+
+    /// A file without content, the tests don't run in a browser that could create one
+    #[cfg(test)]
+    pub(crate) fn fake(name: &str, mime_type: &str, size: u64) -> Self {
+        use wasm_bindgen::JsCast;
+
+        FormFile {
+            name: AttrValue::from(name.to_owned()),
+            size,
+            mime_type: AttrValue::from(mime_type.to_owned()),
+            file: JsValue::NULL.unchecked_into(),
+        }
+    }
+}
+
+impl From<web_sys::File> for FormFile {
+    fn from(file: web_sys::File) -> Self {
+        FormFile {
+            name: file.name().into(),
+            size: file.size() as u64,
+            mime_type: file.type_().into(),
+            file,
+        }
+    }
+}
+
+/// The files of a file input, in the order they were selected.
+///
+/// Its input is the names of the files, separated by `, `. Only an empty input can be set,
+/// clearing the files, the files themselves are set with [`Form::set_files`](crate::Form::set_files).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FormFiles(Vec<FormFile>);
+
+impl FormFiles {
+    pub fn files(&self) -> &[FormFile] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FormFile> {
+        self.0.iter()
+    }
+
+    pub fn push(&mut self, file: FormFile) {
+        self.0.push(file);
+    }
+
+    pub fn remove(&mut self, index: usize) -> FormFile {
+        self.0.remove(index)
+    }
+}
+
+impl From<Vec<FormFile>> for FormFiles {
+    fn from(files: Vec<FormFile>) -> Self {
+        FormFiles(files)
+    }
+}
+
+impl From<&FileList> for FormFiles {
+    fn from(list: &FileList) -> Self {
+        FormFiles(
+            (0..list.length())
+                .filter_map(|index| list.get(index))
+                .map(FormFile::from)
+                .collect(),
+        )
+    }
+}
+
+impl FormValue for FormFiles {
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        field_path.is_empty().then(|| {
+            self.iter()
+                .map(|file| file.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+                .into()
+        })
+    }
+
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        if !field_path.is_empty() {
+            return Err(ConversionError::unknown_field::<Self>(field_path, value));
+        }
+
+        match value.is_empty() {
+            true => self.0.clear(),
+            false if self.value("") == value => {}
+            false => return Err(ConversionError::invalid::<Self>(value)),
+        }
+
+        Ok(())
+    }
+
+    fn files(&self, field_path: &str) -> Option<&FormFiles> {
+        field_path.is_empty().then_some(self)
+    }
+
+    fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+        field_path.is_empty().then_some(self)
+    }
+
+    fn kind(field_path: &str) -> Option<ValueKind> {
+        field_path.is_empty().then_some(ValueKind::Files)
+    }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        match value.is_empty() {
+            true => Ok(Default::default()),
+            false => Err(ConversionError::invalid::<Self>(value)),
+        }
+    }
+
+    fn new_value() -> Option<Self> {
+        Some(Default::default())
+    }
+}
+
+/// What the files of a [`FormFiles`] field must satisfy, rules that are [`None`] are not checked.
+///
+/// A broken rule makes the field invalid with one of these codes, for a [`MessageCatalog`](crate::MessageCatalog):
+/// - `file_count`: too few or too many files, with the params `min`, `max` and `count`
+/// - `file_type`: a file doesn't match `accept`, with the params `accept` and `name`
+/// - `file_size`: a file is larger than `max_size`, with the params `max`, `size` and `name`
+/// - `file_total_size`: the files are larger than `max_total_size` together, with the params `max` and `size`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileRules {
+    /// The largest size of a file, in bytes
+    pub max_size: Option<u64>,
+    /// The largest size of all the files together, in bytes
+    pub max_total_size: Option<u64>,
+    /// The accepted types in the format of the `accept` attribute of a file input,
+    /// e.g. `image/*,.pdf`. The [`File`](crate::File) component uses it when it has no `accept`,
+    /// and checks the files against its own `accept` when this is [`None`].
+    pub accept: Option<AttrValue>,
+    pub min_files: Option<usize>,
+    pub max_files: Option<usize>,
+}
+
+impl FileRules {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn with_max_total_size(mut self, bytes: u64) -> Self {
+        self.max_total_size = Some(bytes);
+        self
+    }
+
+    pub fn with_accept(mut self, accept: impl Into<AttrValue>) -> Self {
+        self.accept = Some(accept.into());
+        self
+    }
+
+    pub fn with_min_files(mut self, count: usize) -> Self {
+        self.min_files = Some(count);
+        self
+    }
+
+    pub fn with_max_files(mut self, count: usize) -> Self {
+        self.max_files = Some(count);
+        self
+    }
+
+    /// Returns the error of the first rule that `files` break
+    pub fn check(&self, files: &FormFiles) -> Result<(), ValidationError> {
+        let count = files.len();
+
+        let message = match (self.min_files, self.max_files) {
+            (Some(min), _) if count < min => {
+                Some(format!("Select at least {}", plural(min, "file")))
+            }
+            (_, Some(max)) if count > max => {
+                Some(format!("Select at most {}", plural(max, "file")))
+            }
+            _ => None,
+        };
+
+        if let Some(message) = message {
+            let mut error = file_error("file_count", message);
+            error.add_param("count".into(), &count);
+            error.add_param("min".into(), &self.min_files);
+            error.add_param("max".into(), &self.max_files);
+
+            return Err(error);
+        }
+
+        if let Some(accept) = &self.accept {
+            if let Some(file) = files
+                .iter()
+                .find(|file| !accepts(accept, &file.name, &file.mime_type))
+            {
+                let mut error = file_error(
+                    "file_type",
+                    format!("{} is not an accepted type of file", file.name),
+                );
+                error.add_param("accept".into(), &accept.as_str());
+                error.add_param("name".into(), &file.name.as_str());
+
+                return Err(error);
+            }
+        }
+
+        if let Some(max) = self.max_size {
+            if let Some(file) = files.iter().find(|file| file.size > max) {
+                let message = format!("{} is larger than {}", file.name, format_size(max));
+                let mut error = file_error("file_size", message);
+                error.add_param("max".into(), &max);
+                error.add_param("size".into(), &file.size);
+                error.add_param("name".into(), &file.name.as_str());
+
+                return Err(error);
+            }
+        }
+
+        if let Some(max) = self.max_total_size {
+            let size: u64 = files.iter().map(|file| file.size).sum();
+
+            if size > max {
+                let message = format!("The files are larger than {} together", format_size(max));
+                let mut error = file_error("file_total_size", message);
+                error.add_param("max".into(), &max);
+                error.add_param("size".into(), &size);
+
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn file_error(code: &'static str, message: String) -> ValidationError {
+    ValidationError {
+        message: Some(Cow::Owned(message)),
+        ..ValidationError::new(code)
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+/// Sizes in bytes, KB, MB or GB, where a KB is 1024 bytes
+fn format_size(bytes: u64) -> String {
+    let units = ["KB", "MB", "GB"];
+    let mut size = bytes as f64;
+
+    if bytes < 1024 {
+        return plural(bytes as usize, "byte");
+    }
+
+    for unit in units {
+        size /= 1024.0;
+
+        if size < 1024.0 || unit == "GB" {
+            return format!("{} {}", (size * 10.0).round() / 10.0, unit);
+        }
+    }
+
+    unreachable!()
+}
+
+/// Matches a file like a browser matches the `accept` attribute: by extension, MIME type or type wildcard
+fn accepts(accept: &str, name: &str, mime_type: &str) -> bool {
+    let name = name.to_lowercase();
+    let mime_type = mime_type.to_lowercase();
+
+    accept
+        .split(',')
+        .map(|token| token.trim().to_lowercase())
+        .filter(|token| !token.is_empty())
+        .any(|token| match token.strip_suffix("/*") {
+            _ if token.starts_with('.') => name.ends_with(&token),
+            Some(prefix) => mime_type.split('/').next() == Some(prefix),
+            None => mime_type == token,
+        })
+}
+
+/// The file rules of a form, per field path pattern
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct FileRuleSet {
+    /// Field path patterns, where `*` matches any single segment
+    fields: Vec<(AttrValue, FileRules)>,
+}
+
+impl FileRuleSet {
+    pub(crate) fn set(&mut self, pattern: AttrValue, rules: FileRules) {
+        self.fields.retain(|(p, _)| *p != pattern);
+        self.fields.push((pattern, rules));
+    }
+
+    /// The rules of `field_path`, from the most recently set matching pattern
+    pub(crate) fn get(&self, field_path: &str) -> Option<&FileRules> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches_field_pattern(pattern, field_path))
+            .map(|(_, rules)| rules)
+    }
+}
+
+/// A part of a `multipart/form-data` body
+#[derive(Debug, PartialEq)]
+pub(crate) enum MultipartPart {
+    Text(AttrValue, AttrValue),
+    File(AttrValue, FormFile),
+}
+
+/// The parts of the fields of `model` in the order they are declared, a part per file for files
+pub(crate) fn multipart_parts<T: FormValue>(model: &T) -> Vec<MultipartPart> {
+    let mut paths = vec![];
+    model.fields("", &mut paths);
+
+    paths
+        .into_iter()
+        .flat_map(|path| match model.files(&path) {
+            Some(files) => files
+                .iter()
+                .map(|file| MultipartPart::File(path.clone(), file.clone()))
+                .collect(),
+            None => vec![MultipartPart::Text(path.clone(), model.value(&path))],
+        })
+        .collect()
+}
+
+pub(crate) fn to_form_data<T: FormValue>(model: &T) -> Result<FormData, JsValue> {
+    let data = FormData::new()?;
+
+    for part in multipart_parts(model) {
+        match part {
+            MultipartPart::Text(name, value) => data.append_with_str(&name, &value)?,
+            MultipartPart::File(name, file) => {
+                data.append_with_blob_and_filename(&name, &file.file, &file.name)?
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accepts, format_size, multipart_parts, FileRules, MultipartPart};
+    use crate::fixtures::profile;
+    use crate::form_state::FormState;
+    use crate::model::FormValue as _;
+    use yew::AttrValue;
+
+    #[test]
+    fn test_form_files() {
+        assert_eq!(
+            multipart_parts(&profile()),
+            [MultipartPart::Text("name".into(), "Ann".into())]
+        );
+
+        let mut state = FormState::new(profile());
+        assert!(state.model.files("avatar").is_some());
+        assert!(state.model.files("name").is_none());
+
+        state.set_value("avatar", "C:\\fakepath\\x.png");
+        assert!(!state.field_valid("avatar"));
+        state.set_value("avatar", "");
+        assert!(state.field_valid("avatar"));
+
+        // Files can't be restored from their input
+        state.set_value("name", "Bob");
+        state.set_value("avatar", "C:\\fakepath\\x.png");
+        assert_eq!(
+            state.draft_values(),
+            [(AttrValue::from("name"), AttrValue::from("Bob"))]
+        );
+
+        let snapshot = state.snapshot();
+        let mut state = FormState::new(profile());
+        state.restore(snapshot);
+        assert_eq!(state.field("name").value(), "Bob");
+        assert_eq!(state.field("avatar").value(), "");
+        assert!(state.field_valid("avatar"));
+    }
+
+    #[test]
+    fn test_file_rules() {
+        assert!(accepts("image/*, .PDF", "scan.pdf", ""));
+        assert!(accepts("image/*,.pdf", "me.png", "image/png"));
+        assert!(accepts("text/csv", "export", "text/csv"));
+        assert!(!accepts("image/*,.pdf", "notes.txt", "text/plain"));
+
+        assert_eq!(format_size(1), "1 byte");
+        assert_eq!(format_size(2 * 1024 * 1024), "2 MB");
+        assert_eq!(format_size(1536), "1.5 KB");
+
+        let mut state = FormState::new(profile());
+        state.set_file_rules("avatar".into(), FileRules::new().with_min_files(1));
+        assert!(!state.validate());
+        assert_eq!(state.field_message("avatar"), "Select at least 1 file");
+        assert_eq!(
+            state
+                .field("avatar")
+                .validation_error
+                .as_ref()
+                .unwrap()
+                .code,
+            "file_count"
+        );
+        assert!(state.field_valid("name"));
+    }
+}
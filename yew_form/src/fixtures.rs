@@ -0,0 +1,153 @@
+//! Models shared by the tests of the modules working on a [`FormState`](crate::form_state::FormState)
+//! or rendering a [`Form`](crate::Form)
+
+use std::collections::BTreeMap;
+
+use validator::Validate;
+
+use crate::{FormFiles, FormValue, Model};
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Line {
+    #[validate(length(min = 1))]
+    pub name: String,
+    pub amount: u32,
+}
+
+pub(crate) fn line(name: &str, amount: u32) -> Line {
+    Line {
+        name: name.into(),
+        amount,
+    }
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Invoice {
+    #[validate]
+    pub lines: Vec<Line>,
+}
+
+pub(crate) fn invoice(lines: &[(&str, u32)]) -> Invoice {
+    Invoice {
+        lines: lines
+            .iter()
+            .map(|(name, amount)| line(name, *amount))
+            .collect(),
+    }
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Translations {
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(FormValue, PartialEq, Clone, Debug)]
+pub(crate) enum Payment {
+    Card { number: String, cvc: u16 },
+    Iban { iban: String },
+    Cash,
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Order {
+    pub payment: Payment,
+}
+
+pub(crate) fn card_order() -> Order {
+    Order {
+        payment: Payment::Card {
+            number: "".into(),
+            cvc: 0,
+        },
+    }
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Product {
+    pub code: String,
+    pub price: f64,
+    pub stock: u32,
+    pub discount: Option<f64>,
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Address {
+    #[validate(length(min = 1))]
+    pub city: String,
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Profile {
+    pub name: String,
+    pub avatar: FormFiles,
+    pub attachments: Vec<FormFiles>,
+}
+
+pub(crate) fn profile() -> Profile {
+    Profile {
+        name: "Ann".into(),
+        avatar: FormFiles::default(),
+        attachments: vec![],
+    }
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Shipment {
+    #[validate]
+    pub shipping: Option<Address>,
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Signup {
+    #[validate(length(min = 3))]
+    pub login: String,
+}
+
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct Account {
+    #[validate(length(min = 1))]
+    pub name: String,
+    pub age: u32,
+}
+
+/// A later version of [`Account`], with a field more
+#[derive(Model, Validate, PartialEq, Clone, Debug)]
+pub(crate) struct AccountV2 {
+    pub name: String,
+    pub age: u32,
+    pub email: String,
+}
+
+/// The first version of a model whose rows changed the type of a field
+pub(crate) mod v1 {
+    use validator::Validate;
+
+    use crate::Model;
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    pub(crate) struct Line {
+        pub amount: u32,
+    }
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    pub(crate) struct Order {
+        pub lines: Vec<Line>,
+    }
+}
+
+/// The second version of the model of [`v1`]
+pub(crate) mod v2 {
+    use validator::Validate;
+
+    use crate::Model;
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    pub(crate) struct Line {
+        pub amount: f64,
+    }
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    pub(crate) struct Order {
+        pub lines: Vec<Line>,
+    }
+}
@@ -1,17 +1,33 @@
+use crate::async_validators::AsyncValidator;
+use crate::error::{ExternalErrors, FormError};
+use crate::files::{self, FileRules, FormFiles};
 use crate::form_field::FormField;
 use crate::form_state::FormState;
+use crate::messages::MessageCatalog;
+use crate::numbers::NumberFormat;
+use crate::paths::{FieldOf, ListOf, MapOf, RowsOf, ValueOf};
+use crate::snapshot::FormSnapshot;
+use crate::urlencoded;
 use crate::Model;
 use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::convert::AsRef;
 use std::fmt::Debug;
+use std::future::Future;
 use std::rc::Rc;
+use std::time::Duration;
+use validator::ValidationError;
+use wasm_bindgen::JsValue;
+use web_sys::FormData;
 use yew::html::ImplicitClone;
+use yew::platform::spawn_local;
 use yew::prelude::*;
 
 #[derive(Clone)]
 pub struct Form<T: Model> {
     state: Rc<RefCell<FormState<T>>>,
     generation: UseStateHandle<u32>,
+    /// The latest generation, which a clone kept by a running check can't tell from its handle
+    latest: Rc<Cell<u32>>,
 }
 
 impl<T: Model> ImplicitClone for Form<T> {}
@@ -36,53 +52,401 @@ where
 
 #[hook]
 pub fn use_form_with_deps<T, D>(init_fn: impl FnOnce(&D) -> T, deps: D) -> Form<T>
+where
+    T: Model,
+    D: PartialEq + 'static,
+{
+    use_form_state(|d| FormState::new(init_fn(d)), deps)
+}
+
+#[hook]
+pub(crate) fn use_form_state<T, D>(init_fn: impl FnOnce(&D) -> FormState<T>, deps: D) -> Form<T>
 where
     T: Model,
     D: PartialEq + 'static,
 {
     Form {
-        state: use_memo(|d| RefCell::new(FormState::new(init_fn(d))), deps),
+        state: use_memo(|d| RefCell::new(init_fn(d)), deps),
         generation: use_state(|| 0),
+        latest: use_memo(|_| Cell::new(0), ()),
     }
 }
 
 impl<T: Model> Form<T> {
-    fn state(&self) -> Ref<FormState<T>> {
+    fn state(&self) -> Ref<'_, FormState<T>> {
         self.state.as_ref().borrow()
     }
 
-    fn state_mut(&self) -> RefMut<FormState<T>> {
+    fn state_mut(&self) -> RefMut<'_, FormState<T>> {
         (*self.state).borrow_mut()
     }
 
-    pub fn value<S: AsRef<str>>(&self, field: S) -> Ref<AttrValue> {
+    pub fn value<S: FieldOf<T>>(&self, field: S) -> Ref<'_, AttrValue> {
         Ref::map(self.field(field.as_ref()), |f| f.value())
     }
 
+    /// Like [`value`](Self::value), but returns an error instead of panicking if the field doesn't exist
+    pub fn try_value<S: FieldOf<T>>(&self, field: S) -> Result<Ref<'_, AttrValue>, FormError> {
+        Ok(Ref::map(self.try_field(field)?, |f| f.value()))
+    }
+
     /// Returns [`Some(..)`] when the field is dirty, else returns [`None`]
-    pub fn dirty_value<S: AsRef<str>>(&self, field: S) -> Option<Ref<AttrValue>> {
+    pub fn dirty_value<S: FieldOf<T>>(&self, field: S) -> Option<Ref<'_, AttrValue>> {
         let field = self.field(field.as_ref());
         field.dirty().then(|| Ref::map(field, |f| f.value()))
     }
 
+    /// Panics if the field doesn't exist, does nothing if it is disabled
     pub fn set_value<S, V>(&self, field: S, value: V)
     where
-        S: AsRef<str>,
+        S: FieldOf<T>,
         V: Into<AttrValue> + AsRef<str>,
     {
         if self.state_mut().set_value(field.as_ref(), value) {
+            self.run_checks(Some(field.as_ref()));
             self.inc_generation()
         }
     }
 
-    pub fn field<S: AsRef<str>>(&self, field: S) -> Ref<FormField> {
+    /// Like [`set_value`](Self::set_value), but returns an error if the field doesn't exist, is disabled
+    /// or if the input can't be converted. In the last case the field still shows the input.
+    pub fn try_set_value<S, V>(&self, field: S, value: V) -> Result<(), FormError>
+    where
+        S: FieldOf<T>,
+        V: Into<AttrValue> + AsRef<str>,
+    {
+        let result = self.state_mut().try_set_value(field.as_ref(), value);
+
+        if matches!(result, Ok(true) | Err(FormError::Conversion(_))) {
+            self.run_checks(Some(field.as_ref()));
+            self.inc_generation();
+        }
+
+        result.map(|_| ())
+    }
+
+    /// The files of a [`FormFiles`] field, panics if the field doesn't hold files
+    pub fn files<S: ValueOf<T, FormFiles>>(&self, field: S) -> FormFiles {
+        self.try_files(field).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`files`](Self::files), but returns an error instead of panicking
+    pub fn try_files<S: ValueOf<T, FormFiles>>(&self, field: S) -> Result<FormFiles, FormError> {
+        self.state().files(field.as_ref())
+    }
+
+    /// Sets the files of a [`FormFiles`] field, e.g. the files selected in a file input.
+    /// Panics if the field doesn't hold files, does nothing if it is disabled.
+    pub fn set_files<S: ValueOf<T, FormFiles>>(&self, field: S, files: impl Into<FormFiles>) {
+        match self.try_set_files(field, files) {
+            Ok(()) | Err(FormError::Disabled(_)) => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Like [`set_files`](Self::set_files), but returns an error if the field doesn't hold files or is disabled
+    pub fn try_set_files<S: ValueOf<T, FormFiles>>(
+        &self,
+        field: S,
+        files: impl Into<FormFiles>,
+    ) -> Result<(), FormError> {
+        self.state_mut().set_files(field.as_ref(), files.into())?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Checks the files of the [`FormFiles`] fields matching `pattern` against `rules`, when they change
+    /// or the form is validated. A `*` segment matches any index or key, e.g. `lines.*.attachment`.
+    pub fn set_file_rules<S: AsRef<str>>(&self, pattern: S, rules: FileRules) {
+        self.state_mut()
+            .set_file_rules(pattern.as_ref().to_owned().into(), rules);
+        self.inc_generation();
+    }
+
+    pub(crate) fn file_rules<S: AsRef<str>>(&self, field: S) -> Option<FileRules> {
+        self.state().file_rules(field.as_ref()).cloned()
+    }
+
+    /// Disables or enables `field`, the components don't accept input for a disabled field
+    pub fn set_disabled<S: FieldOf<T>>(&self, field: S, disabled: bool) {
+        self.state_mut().set_disabled(field.as_ref(), disabled);
+        self.inc_generation();
+    }
+
+    /// Appends `value` to the list at `field`, panics if it is not a list of `V`.
+    /// With a typed path, a value that isn't a row of the list doesn't compile:
+    ///
+    /// ```compile_fail
+    /// # use yew_form::{Form, Model};
+    /// # use validator::Validate;
+    /// #[derive(Model, Validate, PartialEq, Clone)]
+    /// struct Tags {
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// fn add_tag(form: &Form<Tags>) {
+    ///     form.push(Tags::paths().tags(), 42u32);
+    /// }
+    /// ```
+    pub fn push<S: ListOf<T, V>, V: 'static>(&self, field: S, value: V) {
+        self.try_push(field, value)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`push`](Self::push), but returns an error instead of panicking
+    pub fn try_push<S: ListOf<T, V>, V: 'static>(
+        &self,
+        field: S,
+        value: V,
+    ) -> Result<(), FormError> {
+        self.state_mut().push(field.as_ref(), value)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Inserts `value` at `index` in the list at `field`, shifting all rows after it.
+    /// Panics if it is not a list of `V` or if `index` is past its end.
+    pub fn insert<S: ListOf<T, V>, V: 'static>(&self, field: S, index: usize, value: V) {
+        self.try_insert(field, index, value)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`insert`](Self::insert), but returns an error instead of panicking
+    pub fn try_insert<S: ListOf<T, V>, V: 'static>(
+        &self,
+        field: S,
+        index: usize,
+        value: V,
+    ) -> Result<(), FormError> {
+        self.state_mut().insert(field.as_ref(), index, value)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Removes the row at `index` from the list at `field`, shifting all rows after it.
+    /// Panics if it is not a list or has no row at `index`.
+    pub fn remove<S: RowsOf<T>>(&self, field: S, index: usize) {
+        self.try_remove(field, index)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`remove`](Self::remove), but returns an error instead of panicking
+    pub fn try_remove<S: RowsOf<T>>(&self, field: S, index: usize) -> Result<(), FormError> {
+        self.state_mut().remove(field.as_ref(), index)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Swaps the rows at `a` and `b` of the list at `field`, panics if it is not a list or a row doesn't exist
+    pub fn swap<S: RowsOf<T>>(&self, field: S, a: usize, b: usize) {
+        self.try_swap(field, a, b)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`swap`](Self::swap), but returns an error instead of panicking
+    pub fn try_swap<S: RowsOf<T>>(&self, field: S, a: usize, b: usize) -> Result<(), FormError> {
+        self.state_mut().swap(field.as_ref(), a, b)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Inserts `value` under `key` in the map at `field`, replacing any existing entry.
+    /// Panics if it is not a map of `V`.
+    pub fn insert_key<S: MapOf<T, V>, V: 'static>(&self, field: S, key: &str, value: V) {
+        self.try_insert_key(field, key, value)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`insert_key`](Self::insert_key), but returns an error instead of panicking
+    pub fn try_insert_key<S: MapOf<T, V>, V: 'static>(
+        &self,
+        field: S,
+        key: &str,
+        value: V,
+    ) -> Result<(), FormError> {
+        self.state_mut().insert_key(field.as_ref(), key, value)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Moves the entry under `from` to `to` in the map at `field`, keeping its field state.
+    /// Panics if it is not a map, if `from` doesn't exist or if `to` already exists.
+    pub fn rename_key<S: FieldOf<T>>(&self, field: S, from: &str, to: &str) {
+        self.try_rename_key(field, from, to)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`rename_key`](Self::rename_key), but returns an error instead of panicking
+    pub fn try_rename_key<S: FieldOf<T>>(
+        &self,
+        field: S,
+        from: &str,
+        to: &str,
+    ) -> Result<(), FormError> {
+        self.state_mut().rename_key(field.as_ref(), from, to)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    /// Removes the entry under `key` from the map at `field`, panics if it is not a map or `key` doesn't exist
+    pub fn remove_key<S: FieldOf<T>>(&self, field: S, key: &str) {
+        self.try_remove_key(field, key)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`remove_key`](Self::remove_key), but returns an error instead of panicking
+    pub fn try_remove_key<S: FieldOf<T>>(&self, field: S, key: &str) -> Result<(), FormError> {
+        self.state_mut().remove_key(field.as_ref(), key)?;
+        self.run_checks(Some(field.as_ref()));
+        self.inc_generation();
+        Ok(())
+    }
+
+    pub fn field<S: FieldOf<T>>(&self, field: S) -> Ref<'_, FormField> {
         Ref::map(self.state(), |s| s.field(field.as_ref()))
     }
 
+    /// Like [`field`](Self::field), but returns an error instead of panicking if the field doesn't exist
+    pub fn try_field<S: FieldOf<T>>(&self, field: S) -> Result<Ref<'_, FormField>, FormError> {
+        let state = self.state();
+        state.try_field(field.as_ref())?;
+
+        Ok(Ref::map(state, |s| s.field(field.as_ref())))
+    }
+
+    /// Resolves the messages of invalid fields with `catalog`, see [`MessageCatalog`]
+    pub fn set_messages(&self, catalog: impl MessageCatalog + 'static) {
+        self.state_mut().set_messages(Rc::new(catalog));
+        self.inc_generation();
+    }
+
+    /// Switches the locale of the messages, without touching the values or validation state
+    pub fn set_locale(&self, locale: impl Into<AttrValue>) {
+        self.state_mut().set_locale(locale);
+        self.inc_generation();
+    }
+
+    pub fn locale(&self) -> AttrValue {
+        self.state().locale().clone()
+    }
+
+    pub(crate) fn text(&self, key: &str, default: &'static str) -> AttrValue {
+        self.state().text(key, default)
+    }
+
+    /// Shows and parses the number fields of the form in `format`, e.g. `1.234,50`
+    pub fn set_number_format(&self, format: NumberFormat) {
+        self.state_mut().set_number_format(format);
+        self.inc_generation();
+    }
+
+    /// The number format `field` is shown in, if any
+    pub(crate) fn number_format<S: AsRef<str>>(&self, field: S) -> Option<NumberFormat> {
+        self.state().number_format(field.as_ref())
+    }
+
+    /// Shows and parses the fields matching `pattern` in `format`, overriding the format of the form.
+    /// A `*` segment matches any index or key, e.g. `lines.*.price`.
+    pub fn set_field_number_format<S: AsRef<str>>(&self, pattern: S, format: NumberFormat) {
+        self.state_mut()
+            .set_field_number_format(pattern.as_ref().to_owned().into(), format);
+        self.inc_generation();
+    }
+
+    /// The inputs, initial values and messages of the fields, to [`restore`](Self::restore) them later
+    pub fn snapshot(&self) -> FormSnapshot {
+        self.state().snapshot()
+    }
+
+    /// Brings the fields back to the state of `snapshot`, including whether they are dirty and their messages.
+    /// Fields that were added since the snapshot keep their state, fields that no longer exist are skipped.
+    pub fn restore(&self, snapshot: FormSnapshot) {
+        self.state_mut().restore(snapshot);
+        self.inc_generation();
+    }
+
+    /// A `multipart/form-data` body with the fields of the model and the files of its [`FormFiles`],
+    /// to be sent with `fetch`. Empty file inputs are left out.
+    pub fn to_multipart(&self) -> Result<FormData, JsValue> {
+        files::to_form_data(&*self.model())
+    }
+
+    /// Encodes the model as `application/x-www-form-urlencoded`, see [`Model::to_urlencoded`]
+    pub fn to_urlencoded(&self) -> String {
+        self.model().to_urlencoded()
+    }
+
+    /// Sets the values of `application/x-www-form-urlencoded` data as if they were entered,
+    /// see the [`urlencoded`](crate::urlencoded) module. The fields show the inputs that can't be converted,
+    /// the errors of those inputs and of the paths that don't exist are returned.
+    pub fn load_urlencoded(&self, query: &str) -> Vec<FormError> {
+        let errors = urlencoded::load(&mut self.state_mut(), query);
+
+        self.run_checks(None);
+        self.inc_generation();
+        errors
+    }
+
+    /// Validates the model and starts the async checks of the fields that are valid otherwise.
+    /// Returns `false` while checks are running, see [`validate_async`](Self::validate_async).
     pub fn validate(&self) -> bool {
-        let valid = self.state_mut().validate();
+        self.state_mut().validate();
+        self.run_checks(None);
+        self.inc_generation();
+        self.valid()
+    }
+
+    /// Like [`validate`](Self::validate), but waits for the async checks to finish, e.g. before submitting
+    pub async fn validate_async(&self) -> bool {
+        self.validate();
+
+        loop {
+            let waiter = self.state_mut().wait_checks();
+
+            match waiter {
+                Some(waiter) => {
+                    let _ = waiter.await;
+                }
+                None => return self.valid(),
+            }
+        }
+    }
+
+    /// Checks the fields matching `pattern` with `check` once their value is valid otherwise
+    /// and left unchanged for `delay`, see [`async_validators`](crate::async_validators).
+    /// A `*` segment matches any index or key.
+    pub fn set_async_validator<S, F, Fut>(&self, pattern: S, delay: Duration, check: F)
+    where
+        S: AsRef<str>,
+        F: Fn(AttrValue) -> Fut + 'static,
+        Fut: Future<Output = Result<(), ValidationError>> + 'static,
+    {
+        let fields = self.state_mut().set_async_validator(
+            pattern.as_ref().to_owned().into(),
+            AsyncValidator::new(delay, check),
+        );
+
+        for field in fields {
+            self.run_checks(Some(&field));
+        }
+        self.inc_generation();
+    }
+
+    /// Shows errors found outside the form, e.g. by the server, on the fields at their paths.
+    /// An error stays until the value of its field changes or `set_errors` is called again,
+    /// see [`FormField::external`](crate::form_field::FormField::external).
+    /// Returns the errors of paths that aren't fields of the form.
+    pub fn set_errors(&self, errors: impl Into<ExternalErrors>) -> ExternalErrors {
+        let unmatched = self.state_mut().set_errors(errors.into());
         self.inc_generation();
-        valid
+        unmatched
     }
 
     pub fn valid(&self) -> bool {
@@ -93,7 +457,7 @@ impl<T: Model> Form<T> {
         self.state().dirty()
     }
 
-    pub fn model(&self) -> Ref<T> {
+    pub fn model(&self) -> Ref<'_, T> {
         Ref::map(self.state(), |s| s.model())
     }
 
@@ -101,11 +465,41 @@ impl<T: Model> Form<T> {
     /// So differences are registered as dirty and are immediatly validated.
     pub fn update(&self, model: &T) {
         self.state_mut().update(model);
+        self.run_checks(None);
         self.inc_generation();
     }
 
+    /// Changes whenever the form changes
+    pub(crate) fn generation(&self) -> u32 {
+        *self.generation
+    }
+
+    pub(crate) fn draft_values(&self) -> Vec<(AttrValue, AttrValue)> {
+        self.state().draft_values()
+    }
+
     fn inc_generation(&self) {
-        self.generation.set((*self.generation).wrapping_add(1));
+        let generation = self.latest.get().wrapping_add(1);
+
+        self.latest.set(generation);
+        self.generation.set(generation);
+    }
+
+    /// Runs the async checks of `field` and the fields under it, or of all the fields, updating the form when they finish
+    fn run_checks(&self, field: Option<&str>) {
+        let checks = self.state_mut().start_checks(field);
+
+        for (field, id, check) in checks {
+            let form = self.clone();
+
+            spawn_local(async move {
+                if let Ok(result) = check.await {
+                    if form.state_mut().finish_check(&field, id, result.err()) {
+                        form.inc_generation();
+                    }
+                }
+            });
+        }
     }
 }
 
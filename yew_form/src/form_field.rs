@@ -1,12 +1,22 @@
 use yew::AttrValue;
 
-#[derive(PartialEq)]
+use validator::ValidationError;
+
+use crate::error::ConversionError;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct FormField {
     pub(crate) name: AttrValue,
     pub(crate) value: AttrValue,
     pub(crate) initial: AttrValue,
     pub(crate) message: AttrValue,
     pub(crate) valid: bool,
+    pub(crate) disabled: bool,
+    pub(crate) pending: bool,
+    /// The validation error was set with `Form::set_errors`
+    pub(crate) external: bool,
+    pub(crate) conversion_error: Option<ConversionError>,
+    pub(crate) validation_error: Option<ValidationError>,
 }
 
 impl FormField {
@@ -19,6 +29,11 @@ impl FormField {
             initial: value.clone(),
             message: Default::default(),
             valid: true,
+            disabled: false,
+            pending: false,
+            external: false,
+            conversion_error: None,
+            validation_error: None,
         }
     }
 
@@ -38,11 +53,38 @@ impl FormField {
         &self.message
     }
 
+    /// The reason the value could not be converted, if that's why the field is invalid
+    pub fn conversion_error(&self) -> Option<&ConversionError> {
+        self.conversion_error.as_ref()
+    }
+
+    /// The first validation error of the field, if any
+    pub fn validation_error(&self) -> Option<&ValidationError> {
+        self.validation_error.as_ref()
+    }
+
+    /// Returns `true` if the validation error was found outside the form,
+    /// see [`Form::set_errors`](crate::Form::set_errors)
+    pub fn external(&self) -> bool {
+        self.external
+    }
+
     pub fn dirty(&self) -> bool {
         self.initial != self.value
     }
 
+    /// A field isn't valid yet while its async check is [`pending`](Self::pending)
     pub fn valid(&self) -> bool {
-        self.valid
+        self.valid && !self.pending
+    }
+
+    /// Returns `true` while an async check of the value runs, see [`async_validators`](crate::async_validators)
+    pub fn pending(&self) -> bool {
+        self.pending
+    }
+
+    /// A disabled field can't be changed, see [`Form::set_disabled`](crate::Form::set_disabled)
+    pub fn disabled(&self) -> bool {
+        self.disabled
     }
 }
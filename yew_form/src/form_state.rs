@@ -1,14 +1,127 @@
+use std::any::type_name;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::rc::Rc;
 
+use crate::async_validators::{AsyncValidator, AsyncValidators, RunningCheck};
+use crate::error::{ConversionError, ExternalErrors, FormError};
+use crate::files::{FileRuleSet, FileRules, FormFiles};
 use crate::form_field::FormField;
+use crate::messages::{MessageCatalog, Messages};
+use crate::model::{
+    join_field_path, matches_field_pattern, split_field_path, FormList, FormMap, FormValue,
+    ValueKind, PRESENT_FIELD, VARIANT_FIELD,
+};
+use crate::numbers::{NumberFormat, NumberFormats};
+use crate::snapshot::FormSnapshot;
 use crate::Model;
-use validator::{ValidationErrors, ValidationErrorsKind};
+use futures::channel::oneshot;
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
 use yew::AttrValue;
 
 #[derive(PartialEq)]
 pub struct FormState<T: Model> {
     pub(crate) model: T,
     fields: HashMap<AttrValue, FormField>,
+    entries: HashMap<AttrValue, FormEntries>,
+    /// The fields of inactive enum variants, by discriminator path and variant
+    drafts: HashMap<AttrValue, HashMap<AttrValue, Draft>>,
+    messages: Messages,
+    numbers: NumberFormats,
+    file_rules: FileRuleSet,
+    async_validators: AsyncValidators,
+    /// The errors set from outside the form, by field, until the field changes
+    external: HashMap<AttrValue, ValidationError>,
+}
+
+/// The fields of a variant, with their path relative to the enum
+type Draft = Vec<(AttrValue, FormField)>;
+
+/// Tracks where the rows of a list or the keys of a map came from, so adding,
+/// removing, renaming or reordering them marks the form as dirty.
+#[derive(PartialEq)]
+struct FormEntries {
+    initial_len: usize,
+    /// The initial path segment of every entry, [`None`] for entries added afterwards
+    origins: HashMap<AttrValue, Option<AttrValue>>,
+}
+
+impl FormEntries {
+    fn new(segments: Vec<String>) -> Self {
+        FormEntries {
+            initial_len: segments.len(),
+            origins: segments
+                .into_iter()
+                .map(AttrValue::from)
+                .map(|segment| (segment.clone(), Some(segment)))
+                .collect(),
+        }
+    }
+
+    fn dirty(&self) -> bool {
+        self.origins.len() != self.initial_len
+            || self
+                .origins
+                .iter()
+                .any(|(segment, origin)| origin.as_ref() != Some(segment))
+    }
+}
+
+/// Returns the path of the enum if `field_path` is the discriminator of its variant
+fn variant_prefix(field_path: &str) -> Option<&str> {
+    if field_path == VARIANT_FIELD {
+        Some("")
+    } else {
+        field_path.strip_suffix(VARIANT_FIELD)?.strip_suffix('.')
+    }
+}
+
+/// Returns true if `field_path` toggles the presence of an optional struct
+fn is_presence_field(field_path: &str) -> bool {
+    field_path == PRESENT_FIELD
+        || matches!(field_path.strip_suffix(PRESENT_FIELD), Some(prefix) if prefix.ends_with('.'))
+}
+
+fn list_segments(len: usize) -> Vec<String> {
+    (0..len).map(|index| index.to_string()).collect()
+}
+
+/// Whether `field_name` is `filter` or a field under it, e.g. a field of a row of the list `filter`.
+/// Without a filter all the fields match.
+fn matches_filter(filter: Option<&str>, field_name: &str) -> bool {
+    match filter {
+        Some(filter) => {
+            field_name == filter
+                || field_name
+                    .strip_prefix(filter)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        }
+        None => true,
+    }
+}
+
+fn out_of_bounds(field_path: &str, index: usize, len: usize) -> FormError {
+    FormError::OutOfBounds {
+        path: field_path.to_owned().into(),
+        index,
+        len,
+    }
+}
+
+/// The path segments of the rows or keys of the collection at `field_path`, if it is one
+fn collection_segments<T: FormValue>(model: &mut T, field_path: &str) -> Option<Vec<String>> {
+    if let Some(list) = model.list_mut(field_path) {
+        return Some(list_segments(list.len()));
+    }
+
+    model.map_mut(field_path).map(|map| map.keys())
+}
+
+fn unknown_key(field_path: &str, key: &str) -> FormError {
+    FormError::UnknownKey {
+        path: field_path.to_owned().into(),
+        key: key.to_owned().into(),
+    }
 }
 
 impl<T: Model> FormState<T> {
@@ -17,16 +130,21 @@ impl<T: Model> FormState<T> {
 
         model.fields("", &mut fields);
 
-        let form = FormState {
+        FormState {
             fields: fields
                 .into_iter()
                 .map(|f| (model.value(&f), f))
                 .map(|(v, f)| (f.clone(), FormField::new(f, v)))
                 .collect(),
+            entries: Default::default(),
+            drafts: Default::default(),
+            messages: Default::default(),
+            numbers: Default::default(),
+            file_rules: Default::default(),
+            async_validators: Default::default(),
+            external: Default::default(),
             model,
-        };
-
-        form
+        }
     }
 
     /// This updates the model but keeps the initial field values.
@@ -36,8 +154,20 @@ impl<T: Model> FormState<T> {
         let mut dirty = false;
 
         model.fields("", &mut fields);
+
+        if fields.iter().any(|f| !self.fields.contains_key(f)) || fields.len() != self.fields.len()
+        {
+            // The shape of the model changed, e.g. rows were added to a list
+            self.update_shape(model.clone(), &fields);
+        }
+
         fields.into_iter().for_each(|f| {
-            dirty |= self.set_value(&f, model.value(&f));
+            let value = match self.number_format(&f) {
+                Some(format) => format.format(&model.value(&f)).into(),
+                None => model.value(&f),
+            };
+
+            dirty |= self.set_value(&f, value);
         });
 
         if dirty {
@@ -45,6 +175,44 @@ impl<T: Model> FormState<T> {
         }
     }
 
+    /// Replaces the model by `model`, whose fields are `fields`, and tracks the rows and keys
+    /// added to or removed from its collections, matching rows by index and entries by key.
+    fn update_shape(&mut self, mut model: T, fields: &[AttrValue]) {
+        let mut prefixes: Vec<&str> = self
+            .fields
+            .keys()
+            .chain(fields)
+            .flat_map(|field| field.match_indices('.').map(move |(i, _)| &field[..i]))
+            .collect();
+        prefixes.sort_unstable();
+        prefixes.dedup();
+
+        // Sorted, so a collection comes before the collections in its rows
+        let changed: Vec<(String, Vec<String>, Vec<String>)> = prefixes
+            .into_iter()
+            .filter_map(|prefix| {
+                let before = collection_segments(&mut self.model, prefix)?;
+                let after = collection_segments(&mut model, prefix)?;
+
+                (before != after).then(|| (prefix.to_owned(), before, after))
+            })
+            .collect();
+
+        self.model = model;
+
+        for (prefix, before, after) in changed {
+            let kept = after.clone();
+
+            self.move_entries(&prefix, before, after, |segment| {
+                kept.iter()
+                    .any(|k| k == segment)
+                    .then(|| segment.to_owned())
+            });
+        }
+
+        self.sync_fields();
+    }
+
     pub(crate) fn model(&self) -> &T {
         &self.model
     }
@@ -54,50 +222,870 @@ impl<T: Model> FormState<T> {
     // }
 
     pub(crate) fn field(&self, name: &str) -> &FormField {
+        self.try_field(name).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub(crate) fn try_field(&self, name: &str) -> Result<&FormField, FormError> {
         self.fields
             .get(name)
-            .expect(&format!("Field {} does not exist", name))
+            .ok_or_else(|| FormError::UnknownField(name.to_owned().into()))
     }
 
     fn field_mut(&mut self, name: &str) -> &mut FormField {
         self.fields
             .get_mut(name)
-            .expect(&format!("Field {} does not exist", name))
+            .unwrap_or_else(|| panic!("Field {} does not exist", name))
     }
 
+    /// Registers the fields that were added to the model and drops the ones that no longer exist.
+    fn sync_fields(&mut self) {
+        let mut fields = vec![];
+
+        self.model.fields("", &mut fields);
+        self.fields.retain(|name, _| fields.contains(name));
+
+        for field in fields {
+            if !self.fields.contains_key(&field) {
+                let value = self.display_value(&field);
+                self.fields
+                    .insert(field.clone(), FormField::new(field, value));
+            }
+        }
+    }
+
+    fn list_mut(&mut self, field_path: &str) -> Result<&mut dyn FormList, FormError> {
+        self.model
+            .list_mut(field_path)
+            .ok_or_else(|| FormError::wrong_type(field_path, "a list"))
+    }
+
+    pub(crate) fn push<V: 'static>(&mut self, field_path: &str, value: V) -> Result<(), FormError> {
+        let index = self.list_mut(field_path)?.len();
+        self.insert(field_path, index, value)
+    }
+
+    /// Appends a [`new_value`](FormValue::new_value) to the list at `field_path`,
+    /// returns `false` if it is not a list or its rows have no new value
+    pub(crate) fn push_new(&mut self, field_path: &str) -> bool {
+        let list = match self.model.list_mut(field_path) {
+            Some(list) => list,
+            None => return false,
+        };
+        let len = list.len();
+
+        if !list.push_new() {
+            return false;
+        }
+
+        self.move_entries(
+            field_path,
+            list_segments(len),
+            list_segments(len + 1),
+            |segment| Some(segment.to_owned()),
+        );
+        true
+    }
+
+    pub(crate) fn insert<V: 'static>(
+        &mut self,
+        field_path: &str,
+        index: usize,
+        value: V,
+    ) -> Result<(), FormError> {
+        let list = self.list_mut(field_path)?;
+        let len = list.len();
+
+        if index > len {
+            return Err(out_of_bounds(field_path, index, len));
+        }
+
+        if list.insert(index, Box::new(value)).is_err() {
+            return Err(FormError::wrong_type(
+                field_path,
+                format!("a list of {}", type_name::<V>()),
+            ));
+        }
+
+        self.move_entries(
+            field_path,
+            list_segments(len),
+            list_segments(len + 1),
+            |segment| {
+                let i: usize = segment.parse().ok()?;
+                Some(if i < index { i } else { i + 1 }.to_string())
+            },
+        );
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, field_path: &str, index: usize) -> Result<(), FormError> {
+        let list = self.list_mut(field_path)?;
+        let len = list.len();
+
+        if index >= len {
+            return Err(out_of_bounds(field_path, index, len));
+        }
+
+        list.remove(index);
+
+        self.move_entries(
+            field_path,
+            list_segments(len),
+            list_segments(len - 1),
+            |segment| {
+                let i: usize = segment.parse().ok()?;
+                match i.cmp(&index) {
+                    Ordering::Less => Some(i.to_string()),
+                    Ordering::Equal => None,
+                    Ordering::Greater => Some((i - 1).to_string()),
+                }
+            },
+        );
+        Ok(())
+    }
+
+    pub(crate) fn swap(&mut self, field_path: &str, a: usize, b: usize) -> Result<(), FormError> {
+        let list = self.list_mut(field_path)?;
+        let len = list.len();
+
+        if let Some(index) = [a, b].into_iter().find(|index| *index >= len) {
+            return Err(out_of_bounds(field_path, index, len));
+        }
+
+        list.swap(a, b);
+
+        self.move_entries(
+            field_path,
+            list_segments(len),
+            list_segments(len),
+            |segment| {
+                let i: usize = segment.parse().ok()?;
+                Some(
+                    if i == a {
+                        b
+                    } else if i == b {
+                        a
+                    } else {
+                        i
+                    }
+                    .to_string(),
+                )
+            },
+        );
+        Ok(())
+    }
+
+    fn map_mut(&mut self, field_path: &str) -> Result<&mut dyn FormMap, FormError> {
+        self.model
+            .map_mut(field_path)
+            .ok_or_else(|| FormError::wrong_type(field_path, "a map"))
+    }
+
+    /// Inserts a [`new_value`](FormValue::new_value) under `key` in the map at `field_path`, unless the key exists.
+    /// Returns `false` if nothing was inserted.
+    pub(crate) fn insert_new_key(&mut self, field_path: &str, key: &str) -> bool {
+        let map = match self.model.map_mut(field_path) {
+            Some(map) => map,
+            None => return false,
+        };
+        let keys = map.keys();
+
+        if keys.iter().any(|k| k == key) || !map.insert_new(key) {
+            return false;
+        }
+
+        let new_keys = map.keys();
+
+        self.move_entries(field_path, keys, new_keys, |segment| {
+            Some(segment.to_owned())
+        });
+        true
+    }
+
+    /// Inserts `value` under `key`, replacing the entry if the key already exists
+    pub(crate) fn insert_key<V: 'static>(
+        &mut self,
+        field_path: &str,
+        key: &str,
+        value: V,
+    ) -> Result<(), FormError> {
+        let map = self.map_mut(field_path)?;
+        let keys = map.keys();
+
+        if map.insert(key, Box::new(value)).is_err() {
+            return Err(FormError::wrong_type(
+                field_path,
+                format!("a map of {}", type_name::<V>()),
+            ));
+        }
+
+        let new_keys = map.keys();
+
+        self.move_entries(field_path, keys, new_keys, |segment| {
+            (segment != key).then(|| segment.to_owned())
+        });
+        Ok(())
+    }
+
+    pub(crate) fn rename_key(
+        &mut self,
+        field_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<(), FormError> {
+        let map = self.map_mut(field_path)?;
+        let keys = map.keys();
+
+        if keys.iter().any(|k| k == to) {
+            return Err(FormError::DuplicateKey {
+                path: field_path.to_owned().into(),
+                key: to.to_owned().into(),
+            });
+        }
+
+        if !map.rename(from, to) {
+            return Err(unknown_key(field_path, from));
+        }
+
+        let new_keys = map.keys();
+
+        self.move_entries(field_path, keys, new_keys, |segment| {
+            Some(if segment == from { to } else { segment }.to_owned())
+        });
+        Ok(())
+    }
+
+    pub(crate) fn remove_key(&mut self, field_path: &str, key: &str) -> Result<(), FormError> {
+        let map = self.map_mut(field_path)?;
+        let keys = map.keys();
+
+        if !map.remove(key) {
+            return Err(unknown_key(field_path, key));
+        }
+
+        let new_keys = map.keys();
+
+        self.move_entries(field_path, keys, new_keys, |segment| {
+            (segment != key).then(|| segment.to_owned())
+        });
+        Ok(())
+    }
+
+    /// Moves the state of every entry of the collection at `field_path` to the path segment returned by `map`.
+    /// Entries mapped to [`None`] were removed, entries in `after` not mapped to were added.
+    /// The fields of the collection are validated again, as errors can depend on the position of a row.
+    fn move_entries(
+        &mut self,
+        field_path: &str,
+        before: Vec<String>,
+        after: Vec<String>,
+        map: impl Fn(&str) -> Option<String>,
+    ) {
+        fn move_paths<V>(
+            paths: &mut HashMap<AttrValue, V>,
+            field_path: &str,
+            map: &impl Fn(&str) -> Option<String>,
+        ) -> Vec<(AttrValue, V)> {
+            let prefix = format!("{}.", field_path);
+            let keys: Vec<AttrValue> = paths
+                .keys()
+                .filter(|k| k.starts_with(&prefix))
+                .cloned()
+                .collect();
+
+            keys.into_iter()
+                .filter_map(|key| {
+                    let value = paths.remove(&key)?;
+                    let (segment, suffix) = split_field_path(&key[prefix.len()..]);
+                    let entry_path = join_field_path(field_path, &map(segment)?);
+
+                    Some((join_field_path(&entry_path, suffix).into(), value))
+                })
+                .collect()
+        }
+
+        let entries = self
+            .entries
+            .remove(field_path)
+            .unwrap_or_else(|| FormEntries::new(before));
+        let mut origins: HashMap<AttrValue, Option<AttrValue>> = entries
+            .origins
+            .into_iter()
+            .filter_map(|(segment, origin)| Some((map(&segment)?.into(), origin)))
+            .collect();
+
+        for segment in after {
+            origins.entry(segment.into()).or_insert(None);
+        }
+
+        for (name, mut field) in move_paths(&mut self.fields, field_path, &map) {
+            field.name = name.clone();
+            self.fields.insert(name, field);
+        }
+
+        for (name, entries) in move_paths(&mut self.entries, field_path, &map) {
+            self.entries.insert(name, entries);
+        }
+
+        for (name, drafts) in move_paths(&mut self.drafts, field_path, &map) {
+            self.drafts.insert(name, drafts);
+        }
+
+        for (name, error) in move_paths(&mut self.external, field_path, &map) {
+            self.external.insert(name, error);
+        }
+
+        let results = self.async_validators.results_mut();
+        for (name, result) in move_paths(results, field_path, &map) {
+            results.insert(name, result);
+        }
+
+        // Running checks resolve for the path they were started for, the form starts them again
+        self.async_validators
+            .cancel_unless(|field_name| !matches_filter(Some(field_path), field_name));
+
+        self.entries.insert(
+            field_path.to_owned().into(),
+            FormEntries {
+                initial_len: entries.initial_len,
+                origins,
+            },
+        );
+
+        self.sync_fields();
+
+        self.fields
+            .values_mut()
+            .filter(|field| matches_filter(Some(field_path), &field.name))
+            .for_each(|field| field.pending = false);
+        self.update_validation_field(field_path);
+    }
+
+    /// Returns `true` if the input of the field changed, panics if the field doesn't exist
     pub(crate) fn set_value<V>(&mut self, field_name: &str, value: V) -> bool
     where
         V: Into<AttrValue> + AsRef<str>,
     {
-        if self.field(field_name).value == value.as_ref() {
-            return false;
+        match self.try_set_value(field_name, value) {
+            Ok(changed) => changed,
+            // The field keeps the input and shows why it is invalid
+            Err(FormError::Conversion(_)) => true,
+            Err(FormError::Disabled(_)) => false,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    pub(crate) fn try_set_value<V>(&mut self, field_name: &str, value: V) -> Result<bool, FormError>
+    where
+        V: Into<AttrValue> + AsRef<str>,
+    {
+        let field = self.try_field(field_name)?;
+
+        if field.disabled {
+            return Err(FormError::Disabled(field.name.clone()));
+        }
+
+        if field.value == value.as_ref() {
+            return Ok(false);
         }
 
         let value = value.into();
-        let result = self.model.set_value(field_name, &value);
+        let variant = variant_prefix(field_name).map(|_| self.model.value(field_name));
+        self.external.remove(field_name);
+        let result = self.set_model_value(field_name, &value);
 
-        let field = self.field_mut(field_name);
-        field.value = value;
+        let messages = &self.messages;
+        let field = self
+            .fields
+            .get_mut(field_name)
+            .expect("Field was checked to exist");
+        field.value = value.clone();
 
         match result {
             Ok(()) => {
+                if let Some(variant) = variant.filter(|variant| *variant != value) {
+                    self.switch_variant(field_name, variant, value);
+                }
+
+                if is_presence_field(field_name) {
+                    self.sync_fields();
+                }
+
                 self.update_validation_field(field_name);
             }
             Err(e) => {
                 field.valid = false;
-                field.message = e.into();
+                field.external = false;
+                field.message = messages.conversion(&e);
+                field.conversion_error = Some(e.clone());
+
+                return Err(FormError::Conversion(e));
             }
         }
 
+        Ok(true)
+    }
+
+    /// Fails with [`FormError::WrongType`] if the field doesn't hold files
+    pub(crate) fn files(&self, field_name: &str) -> Result<FormFiles, FormError> {
+        self.model
+            .files(field_name)
+            .cloned()
+            .ok_or_else(|| FormError::wrong_type(field_name, "files"))
+    }
+
+    pub(crate) fn set_files(
+        &mut self,
+        field_name: &str,
+        files: FormFiles,
+    ) -> Result<(), FormError> {
+        let field = self.try_field(field_name)?;
+
+        if field.disabled {
+            return Err(FormError::Disabled(field.name.clone()));
+        }
+
+        let value = files.value("");
+
+        *self
+            .model
+            .files_mut(field_name)
+            .ok_or_else(|| FormError::wrong_type(field_name, "files"))? = files;
+        self.field_mut(field_name).value = value;
+        self.external.remove(field_name);
+        self.update_validation_field(field_name);
+        Ok(())
+    }
+
+    /// Replaces the external errors, the errors of paths without a field are returned
+    pub(crate) fn set_errors(&mut self, errors: ExternalErrors) -> ExternalErrors {
+        let previous: Vec<AttrValue> = self.external.drain().map(|(path, _)| path).collect();
+        let mut unmatched = ExternalErrors::new();
+
+        for (path, error) in errors.0 {
+            match self.fields.contains_key(&path) {
+                // A field shows its first error
+                true => {
+                    self.external.entry(path).or_insert(error);
+                }
+                false => unmatched.0.push((path, error)),
+            }
+        }
+
+        let changed: Vec<AttrValue> = previous
+            .into_iter()
+            .chain(self.external.keys().cloned())
+            .filter(|path| self.fields.contains_key(path))
+            .collect();
+
+        for path in changed {
+            self.update_validation_field(&path);
+        }
+
+        unmatched
+    }
+
+    pub(crate) fn set_disabled(&mut self, field_name: &str, disabled: bool) {
+        self.field_mut(field_name).disabled = disabled;
+    }
+
+    /// The state of the fields, in the order they are declared
+    pub(crate) fn snapshot(&self) -> FormSnapshot {
+        let mut paths = vec![];
+        self.model.fields("", &mut paths);
+
+        FormSnapshot {
+            fields: paths
+                .iter()
+                .filter_map(|path| self.fields.get(path))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Restores the fields of `snapshot` that still exist, the other fields keep their state.
+    /// The drafts of inactive variants are not part of a snapshot, and files can't be restored from their names.
+    pub(crate) fn restore(&mut self, snapshot: FormSnapshot) {
+        let mut pending = snapshot.fields;
+        // Discriminators and presence toggles decide which other fields exist, so they go first
+        pending.sort_by_key(|field| {
+            variant_prefix(&field.name).is_none() && !is_presence_field(&field.name)
+        });
+
+        while let Some(index) = pending
+            .iter()
+            .position(|field| self.fields.contains_key(&field.name))
+        {
+            let field = pending.remove(index);
+
+            if self.model.files(&field.name).is_some() {
+                continue;
+            }
+
+            let structural =
+                variant_prefix(&field.name).is_some() || is_presence_field(&field.name);
+            let result = self.set_model_value(&field.name, &field.value);
+
+            let name = field.name.clone();
+
+            *self.field_mut(&name) = FormField {
+                conversion_error: result.err(),
+                pending: false,
+                ..field
+            };
+
+            if structural {
+                self.sync_fields();
+            }
+        }
+
+        // The external errors of the snapshot replace the current ones
+        self.external = self
+            .fields
+            .values()
+            .filter(|field| field.external)
+            .filter_map(|field| Some((field.name.clone(), field.validation_error.clone()?)))
+            .collect();
+    }
+
+    /// The inputs that differ from their initial value, in the order they are declared.
+    /// Files are left out, their names can't be entered again.
+    pub(crate) fn draft_values(&self) -> Vec<(AttrValue, AttrValue)> {
+        let mut paths = vec![];
+        self.model.fields("", &mut paths);
+
+        paths
+            .iter()
+            .filter(|path| self.model.files(path).is_none())
+            .filter_map(|path| self.fields.get(path))
+            .filter(|field| field.dirty())
+            .map(|field| (field.name.clone(), field.value.clone()))
+            .collect()
+    }
+
+    /// Sets the inputs of a draft as if they were entered, the fields keep their initial value.
+    /// Inputs of fields that no longer exist are skipped.
+    pub(crate) fn restore_draft(&mut self, values: Vec<(AttrValue, AttrValue)>) {
+        self.try_set_values(values);
+    }
+
+    /// Sets the inputs in the order the fields they create come to exist,
+    /// returns the errors of the inputs that were rejected and of the fields that don't exist.
+    pub(crate) fn try_set_values(&mut self, values: Vec<(AttrValue, AttrValue)>) -> Vec<FormError> {
+        let mut pending = values;
+        let mut errors = vec![];
+        // Discriminators and presence toggles decide which other fields exist, so they go first
+        pending.sort_by_key(|(path, _)| variant_prefix(path).is_none() && !is_presence_field(path));
+
+        while let Some(index) = pending
+            .iter()
+            .position(|(path, _)| self.fields.contains_key(path))
+        {
+            let (path, value) = pending.remove(index);
+
+            if let Err(e) = self.try_set_value(&path, value) {
+                errors.push(e);
+            }
+        }
+
+        errors.extend(
+            pending
+                .into_iter()
+                .map(|(path, _)| FormError::UnknownField(path)),
+        );
+        errors
+    }
+
+    /// Keeps the fields of the variant that was left as a draft and restores the draft of the selected variant, if any.
+    fn switch_variant(&mut self, field_name: &str, previous: AttrValue, selected: AttrValue) {
+        let prefix = variant_prefix(field_name).unwrap_or_default();
+        let keys: Vec<AttrValue> = self
+            .fields
+            .keys()
+            .filter(|k| {
+                *k != field_name && (prefix.is_empty() || k.starts_with(&format!("{}.", prefix)))
+            })
+            .cloned()
+            .collect();
+        let draft: Draft = keys
+            .into_iter()
+            .filter_map(|key| {
+                let field = self.fields.remove(&key)?;
+                let relative = key[prefix.len()..].trim_start_matches('.').to_owned();
+
+                Some((relative.into(), field))
+            })
+            .collect();
+
+        let drafts = self.drafts.entry(field_name.to_owned().into()).or_default();
+        drafts.insert(previous, draft);
+        let draft = drafts.remove(&selected);
+
+        self.sync_fields();
+
+        let mut draft = match draft {
+            Some(draft) => draft,
+            None => return,
+        };
+
+        // Restore the discriminators of nested enums before the fields of their variants
+        draft.sort_by_key(|(relative, _)| {
+            (
+                relative.split('.').count(),
+                variant_prefix(relative).is_none(),
+            )
+        });
+
+        for (relative, mut field) in draft {
+            let path: AttrValue = join_field_path(prefix, &relative).into();
+
+            if !self.fields.contains_key(&path) {
+                continue;
+            }
+
+            if variant_prefix(&path).is_some() {
+                self.set_value(&path, field.value.clone());
+            } else {
+                let _ = self.set_model_value(&path, &field.value);
+            }
+
+            field.name = path.clone();
+            self.fields.insert(path, field);
+        }
+    }
+
+    /// Resolves the messages of invalid fields with `catalog` from now on
+    pub fn set_messages(&mut self, catalog: Rc<dyn MessageCatalog>) {
+        self.messages.set_catalog(catalog);
+        self.update_messages();
+    }
+
+    /// Switches the locale messages are resolved in, the messages of invalid fields are updated immediately
+    pub fn set_locale(&mut self, locale: impl Into<AttrValue>) {
+        self.messages.set_locale(locale.into());
+        self.update_messages();
+    }
+
+    pub fn locale(&self) -> &AttrValue {
+        self.messages.locale()
+    }
+
+    /// The text shown by a component for `key` in the locale of the form, see [`MessageCatalog`]
+    pub(crate) fn text(&self, key: &str, default: &'static str) -> AttrValue {
+        self.messages.text(key, default)
+    }
+
+    /// Shows the numbers of the form in `format`, unless a field has its own format
+    pub fn set_number_format(&mut self, format: NumberFormat) {
+        self.update_number_formats(|numbers| numbers.set_form(format));
+    }
+
+    /// Shows the numbers of the fields matching `pattern` in `format`,
+    /// a `*` segment in the pattern matches any key or index
+    pub fn set_field_number_format(&mut self, pattern: AttrValue, format: NumberFormat) {
+        self.update_number_formats(|numbers| numbers.set_field(pattern, format));
+    }
+
+    /// Changes the number formats and converts the values shown in number fields to the new format
+    fn update_number_formats(&mut self, update: impl FnOnce(&mut NumberFormats)) {
+        let canonical = |format: Option<NumberFormat>, value: &str| match format {
+            Some(format) => format.parse(value),
+            None => Some(value.to_owned()),
+        };
+        let numbers: Vec<(AttrValue, Option<String>, Option<String>)> = self
+            .fields
+            .values()
+            .filter(|field| {
+                matches!(
+                    T::kind(&field.name),
+                    Some(ValueKind::Integer | ValueKind::Number)
+                )
+            })
+            .map(|field| {
+                let format = self.number_format(&field.name);
+                let value =
+                    canonical(format, &field.value).filter(|_| field.conversion_error.is_none());
+
+                (field.name.clone(), value, canonical(format, &field.initial))
+            })
+            .collect();
+
+        update(&mut self.numbers);
+
+        for (name, value, initial) in numbers {
+            let format = self.number_format(&name);
+            let display = |value: String| match format {
+                Some(format) => format.format(&value).into(),
+                None => AttrValue::from(value),
+            };
+            let field = self.field_mut(&name);
+
+            if let Some(value) = value {
+                field.value = display(value);
+            }
+
+            if let Some(initial) = initial {
+                field.initial = display(initial);
+            }
+        }
+    }
+
+    /// Checks the files of the fields matching `pattern` against `rules` from now on,
+    /// a `*` segment in the pattern matches any key or index
+    pub fn set_file_rules(&mut self, pattern: AttrValue, rules: FileRules) {
+        self.file_rules.set(pattern, rules);
+    }
+
+    pub(crate) fn file_rules(&self, field_path: &str) -> Option<&FileRules> {
+        self.file_rules.get(field_path)
+    }
+
+    /// Replaces the validator of the fields matching `pattern` and drops the errors found by the previous one.
+    /// Returns the matching fields, whose checks have to be started again.
+    pub(crate) fn set_async_validator(
+        &mut self,
+        pattern: AttrValue,
+        validator: AsyncValidator,
+    ) -> Vec<AttrValue> {
+        let names: Vec<AttrValue> = self
+            .fields
+            .keys()
+            .filter(|name| matches_field_pattern(&pattern, name))
+            .cloned()
+            .collect();
+
+        self.async_validators.set(pattern, validator);
+
+        for name in &names {
+            self.field_mut(name).pending = false;
+            self.update_validation_field(name);
+        }
+
+        names
+    }
+
+    /// Starts the async checks of `field` and the fields under it, or of all the fields, whose value is valid otherwise,
+    /// cancelling the checks of invalid fields. Returns the checks to run, with their field and id.
+    pub(crate) fn start_checks(
+        &mut self,
+        field_name_filter: Option<&str>,
+    ) -> Vec<(AttrValue, u64, RunningCheck)> {
+        let names: Vec<AttrValue> = self
+            .fields
+            .keys()
+            .filter(|name| matches_filter(field_name_filter, name))
+            .filter(|name| self.async_validators.has(name))
+            .cloned()
+            .collect();
+        let mut checks = vec![];
+
+        for name in names {
+            let check = match self.field(&name).valid {
+                true => {
+                    let value = self.model.value(&name);
+                    self.async_validators.start(&name, value)
+                }
+                false => {
+                    self.async_validators.cancel(&name);
+                    None
+                }
+            };
+
+            self.field_mut(&name).pending = self.async_validators.is_running(&name);
+
+            if let Some((id, check)) = check {
+                checks.push((name, id, check));
+            }
+        }
+
+        checks
+    }
+
+    /// Shows the result of the check `id`, returns `false` if it was cancelled
+    pub(crate) fn finish_check(
+        &mut self,
+        field_name: &str,
+        id: u64,
+        error: Option<ValidationError>,
+    ) -> bool {
+        if !self.async_validators.finish(field_name, id, error) {
+            return false;
+        }
+
+        // The row of the field may have been removed while it was checked
+        if let Some(field) = self.fields.get_mut(field_name) {
+            field.pending = false;
+            self.add_async_errors(Some(field_name));
+        }
+
         true
     }
 
+    /// Resolves once no async check runs, or [`None`] if none runs
+    pub(crate) fn wait_checks(&mut self) -> Option<oneshot::Receiver<()>> {
+        self.async_validators.wait()
+    }
+
+    /// The format of `field_path` if it holds a number, fixed precision only applies to numbers with decimals
+    pub(crate) fn number_format(&self, field_path: &str) -> Option<NumberFormat> {
+        let format = self.numbers.get(field_path)?;
+
+        match T::kind(field_path)? {
+            ValueKind::Number => Some(*format),
+            ValueKind::Integer => Some(NumberFormat {
+                precision: None,
+                ..*format
+            }),
+            _ => None,
+        }
+    }
+
+    /// The value of `field_path` in the model, as it is shown in the form
+    fn display_value(&self, field_path: &str) -> AttrValue {
+        let value = self.model.value(field_path);
+
+        match self.number_format(field_path) {
+            Some(format) => format.format(&value).into(),
+            None => value,
+        }
+    }
+
+    /// Sets a value as it is shown in the form on the model
+    fn set_model_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        let format = match self.number_format(field_path) {
+            Some(format) => format,
+            None => return self.model.set_value(field_path, value),
+        };
+
+        match format.parse(value) {
+            Some(value) => self.model.set_value(field_path, &value),
+            None => {
+                let code = match T::kind(field_path) {
+                    Some(ValueKind::Integer) => "invalid_integer",
+                    _ => "invalid_number",
+                };
+
+                Err(ConversionError::new("number", value.to_owned(), code).prefixed(field_path))
+            }
+        }
+    }
+
+    fn update_messages(&mut self) {
+        for field in self.fields.values_mut() {
+            if let Some(error) = &field.conversion_error {
+                field.message = self.messages.conversion(error);
+            } else if let Some(error) = &field.validation_error {
+                field.message = self.messages.validation(error);
+            }
+        }
+    }
+
     pub fn valid(&self) -> bool {
         self.fields.values().all(FormField::valid)
     }
 
     pub fn dirty(&self) -> bool {
-        self.fields.values().any(FormField::dirty)
+        self.fields.values().any(FormField::dirty) || self.entries.values().any(FormEntries::dirty)
     }
 
     pub fn field_valid(&self, field_path: &str) -> bool {
@@ -121,76 +1109,459 @@ impl<T: Model> FormState<T> {
         if let Err(errors) = self.model.validate() {
             self.add_errors("", None, &errors);
         }
+
+        self.add_file_errors(None);
+        self.add_async_errors(None);
+        self.add_external_errors(None);
     }
 
+    /// Validates `field` and the fields under it, e.g. the fields of the rows of a list
     pub(crate) fn update_validation_field(&mut self, field: &str) {
         self.clear_errors(Some(field));
 
         if let Err(errors) = self.model.validate() {
             self.add_errors("", Some(field), &errors);
         }
+
+        self.add_file_errors(Some(field));
+        self.add_async_errors(Some(field));
+        self.add_external_errors(Some(field));
     }
 
-    fn clear_errors(&mut self, field: Option<&str>) {
-        match field {
-            Some(field) => {
-                let field = self.field_mut(field);
-                field.valid = true;
-                field.message = Default::default();
+    /// Checks the files of the fields with rules against them, fields that are already invalid keep their error
+    fn add_file_errors(&mut self, field_name_filter: Option<&str>) {
+        let checked: Vec<(AttrValue, FileRules)> = self
+            .fields
+            .keys()
+            .filter(|name| matches_filter(field_name_filter, name))
+            .filter_map(|name| Some((name.clone(), self.file_rules.get(name)?.clone())))
+            .collect();
+
+        for (name, rules) in checked {
+            if let Some(Err(error)) = self.model.files(&name).map(|files| rules.check(files)) {
+                self.add_error(&name, error);
             }
-            None => {
-                self.fields.values_mut().for_each(|f| {
-                    f.valid = true;
-                    f.message = Default::default();
-                });
+        }
+    }
+
+    /// Shows the errors that async checks found for the current values
+    fn add_async_errors(&mut self, field_name_filter: Option<&str>) {
+        let errors: Vec<(AttrValue, ValidationError)> = self
+            .fields
+            .keys()
+            .filter(|name| matches_filter(field_name_filter, name))
+            .filter_map(|name| {
+                let error = self.async_validators.error(name, &self.model.value(name))?;
+                Some((name.clone(), error.clone()))
+            })
+            .collect();
+
+        for (name, error) in errors {
+            self.add_error(&name, error);
+        }
+    }
+
+    /// Shows the external errors, once the local validation passes
+    fn add_external_errors(&mut self, field_name_filter: Option<&str>) {
+        let errors: Vec<(AttrValue, ValidationError)> = self
+            .external
+            .iter()
+            .filter(|(name, _)| matches_filter(field_name_filter, name))
+            .filter(|(name, _)| self.fields.contains_key(*name))
+            .map(|(name, error)| (name.clone(), error.clone()))
+            .collect();
+
+        for (name, error) in errors {
+            if self.add_error(&name, error) {
+                self.field_mut(&name).external = true;
             }
         }
     }
 
+    /// Marks the field invalid with `error`, unless it already is. Returns `true` if it wasn't.
+    fn add_error(&mut self, field_name: &str, error: ValidationError) -> bool {
+        let field = self
+            .fields
+            .get_mut(field_name)
+            .expect("Field was checked to exist");
+
+        if !field.valid {
+            return false;
+        }
+
+        field.valid = false;
+        field.message = self.messages.validation(&error);
+        field.validation_error = Some(error);
+        true
+    }
+
+    fn clear_errors(&mut self, field: Option<&str>) {
+        self.fields
+            .values_mut()
+            .filter(|f| matches_filter(field, &f.name))
+            .for_each(|f| {
+                f.valid = true;
+                f.message = Default::default();
+                f.conversion_error = None;
+                f.validation_error = None;
+                f.external = false;
+            });
+    }
+
     fn add_errors(
         &mut self,
         prefix: &str,
         field_name_filter: Option<&str>,
         errors: &ValidationErrors,
     ) {
-        fn generate_field_name(prefix: &str, field_name: &str) -> String {
-            if prefix == "" {
-                String::from(field_name)
-            } else {
-                format!("{}.{}", prefix, field_name)
-            }
-        }
-
         for (field_name, error) in errors.errors() {
-            if let Some(ref field_name_filter) = field_name_filter {
-                if field_name != field_name_filter {
-                    // ignore all fields not matching this field
+            let field_path = join_field_path(prefix, field_name);
+
+            if let Some(field_name_filter) = field_name_filter {
+                if !matches_filter(Some(field_name_filter), &field_path)
+                    && !field_name_filter.starts_with(&format!("{}.", field_path))
+                {
+                    // ignore all fields not matching, containing or under this field
                     continue;
                 }
             }
 
             match error {
-                ValidationErrorsKind::Struct(errors) => self.add_errors(
-                    &generate_field_name(prefix, field_name),
-                    field_name_filter,
-                    errors,
-                ),
-                ValidationErrorsKind::List(_) => { /* Ignore? */ }
+                ValidationErrorsKind::Struct(errors) => {
+                    self.add_errors(&field_path, field_name_filter, errors)
+                }
+                ValidationErrorsKind::List(errors) => {
+                    for (index, errors) in errors {
+                        self.add_errors(
+                            &join_field_path(&field_path, &index.to_string()),
+                            field_name_filter,
+                            errors,
+                        );
+                    }
+                }
                 ValidationErrorsKind::Field(errors) => {
-                    let field = self.field_mut(&generate_field_name(prefix, field_name));
-
-                    field.valid = false;
+                    // Errors on a collection or struct as a whole have no field to show them on
+                    if let Some(field) = self.fields.get_mut(field_path.as_str()) {
+                        field.valid = false;
 
-                    field.message = if let Some(message) = &errors[0].message {
-                        match message {
-                            std::borrow::Cow::Borrowed(msg) => (*msg).into(),
-                            std::borrow::Cow::Owned(msg) => msg.to_owned().into(),
-                        }
-                    } else {
-                        "Error".into()
+                        field.message = self.messages.validation(&errors[0]);
+                        field.validation_error = Some(errors[0].clone());
                     }
                 }
             };
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::FormState;
+    use crate::fixtures::{card_order, invoice, line, Address, Payment, Shipment, Translations};
+    use crate::model::FormValue;
+    use crate::{FormError, MessageMap};
+    use std::rc::Rc;
+    use validator::Validate;
+
+    #[test]
+    fn test_list_rows() {
+        let mut state = FormState::new(invoice(&[("a", 1), ("b", 2)]));
+
+        state.set_value("lines.1.amount", "3");
+        state.set_value("lines.1.name", "");
+        assert!(!state.field_valid("lines.1.name"));
+
+        state.swap("lines", 0, 1).unwrap();
+        assert_eq!(state.model().lines, vec![line("", 3), line("a", 1)]);
+        assert_eq!(state.field("lines.0.amount").initial_value(), "2");
+        assert!(!state.field_valid("lines.0.name"));
+        assert!(state.field_valid("lines.1.name"));
+
+        state.swap("lines", 0, 1).unwrap();
+        state.set_value("lines.1.amount", "2");
+        state.set_value("lines.1.name", "b");
+        assert!(!state.dirty());
+
+        state.push("lines", line("c", 4)).unwrap();
+        assert_eq!(state.field("lines.2.name").value(), "c");
+        assert!(state.dirty());
+
+        state.remove("lines", 0).unwrap();
+        assert_eq!(state.field("lines.0.name").value(), "b");
+        assert_eq!(state.field("lines.1.name").value(), "c");
+        assert!(!state.fields.contains_key("lines.2.name"));
+
+        state.insert("lines", 0, line("a", 1)).unwrap();
+        assert!(state.dirty());
+        state.remove("lines", 2).unwrap();
+        assert!(state.dirty(), "reinserted row is a new row");
+
+        assert_eq!(
+            state.push("lines", "d").unwrap_err().to_string(),
+            "Field lines is not a list of &str"
+        );
+        assert_eq!(
+            state.push("lines.0.name", line("d", 5)).unwrap_err(),
+            FormError::wrong_type("lines.0.name", "a list")
+        );
+        assert_eq!(
+            state.remove("lines", 2).unwrap_err().to_string(),
+            "Row 2 does not exist in lines, which has 2 rows"
+        );
+        assert_eq!(
+            state.insert("lines", 3, line("d", 5)).unwrap_err(),
+            FormError::OutOfBounds {
+                path: "lines".into(),
+                index: 3,
+                len: 2
+            }
+        );
+        assert!(matches!(
+            state.swap("lines", 0, 5),
+            Err(FormError::OutOfBounds { index: 5, .. })
+        ));
+        assert_eq!(state.model().lines.len(), 2);
+
+        // New rows are validated, and rows keep their errors when they move
+        state.push("lines", line("", 0)).unwrap();
+        assert!(!state.field_valid("lines.2.name"));
+        state.remove("lines", 0).unwrap();
+        assert!(!state.field_valid("lines.1.name"));
+        assert!(state.field_valid("lines.0.name"));
+    }
+
+    #[test]
+    fn test_update_rows() {
+        let mut state = FormState::new(invoice(&[("a", 1)]));
+
+        state.update(&invoice(&[("a", 1), ("b", 2)]));
+        assert_eq!(state.field("lines.1.name").value(), "b");
+        assert!(state.dirty());
+
+        state.update(&invoice(&[("a", 1)]));
+        assert!(!state.dirty());
+
+        state.update(&invoice(&[("", 1), ("", 2)]));
+        assert!(!state.field_valid("lines.1.name"));
+
+        state.update(&invoice(&[]));
+        assert!(state.dirty());
+        assert!(!state.fields.contains_key("lines.0.name"));
+    }
+
+    #[test]
+    fn test_map_keys() {
+        let mut state = FormState::new(Translations {
+            labels: [("en".to_owned(), "Hello".to_owned())].into(),
+        });
+
+        state.set_value("labels.en", "Hi");
+        state.rename_key("labels", "en", "en-GB").unwrap();
+        assert_eq!(state.field("labels.en-GB").value(), "Hi");
+        assert_eq!(state.field("labels.en-GB").initial_value(), "Hello");
+        assert!(!state.fields.contains_key("labels.en"));
+
+        state
+            .insert_key("labels", "fr", "Bonjour".to_owned())
+            .unwrap();
+        assert_eq!(state.field("labels.fr").value(), "Bonjour");
+
+        state.remove_key("labels", "fr").unwrap();
+        state.rename_key("labels", "en-GB", "en").unwrap();
+        state.set_value("labels.en", "Hello");
+        assert!(!state.dirty());
+
+        state
+            .insert_key("labels", "fr", "Bonjour".to_owned())
+            .unwrap();
+        assert!(matches!(
+            state.rename_key("labels", "en", "fr"),
+            Err(FormError::DuplicateKey { key, .. }) if key == "fr"
+        ));
+        assert!(matches!(
+            state.remove_key("labels", "de"),
+            Err(FormError::UnknownKey { key, .. }) if key == "de"
+        ));
+        assert!(matches!(
+            state.files("labels.fr"),
+            Err(FormError::WrongType { .. })
+        ));
+        assert_eq!(state.model().labels.len(), 2);
+    }
+
+    #[test]
+    fn test_variant_drafts() {
+        let mut state = FormState::new(card_order());
+
+        state.set_value("payment.number", "4111");
+        state.set_value("payment.cvc", "abc");
+        state.set_value("payment.$variant", "Iban");
+        assert!(!state.fields.contains_key("payment.number"));
+        assert_eq!(
+            state.model().payment,
+            Payment::Iban {
+                iban: String::new()
+            }
+        );
+
+        state.set_value("payment.iban", "BE68");
+        state.set_value("payment.$variant", "Cash");
+        assert_eq!(state.fields.len(), 1);
+
+        state.set_value("payment.$variant", "Card");
+        assert_eq!(state.field("payment.number").value(), "4111");
+        assert_eq!(state.field("payment.cvc").value(), "abc");
+        assert!(!state.field_valid("payment.cvc"));
+        assert_eq!(
+            state.field("payment.cvc").conversion_error().unwrap().path,
+            "payment.cvc"
+        );
+        assert_eq!(
+            state.model().payment,
+            Payment::Card {
+                number: "4111".into(),
+                cvc: 0
+            }
+        );
+
+        state.set_value("payment.$variant", "Iban");
+        assert_eq!(state.field("payment.iban").value(), "BE68");
+
+        let mut payment = card_order().payment;
+        let error = payment.set_value("$variant.0", "Cash").unwrap_err();
+        assert_eq!(error.code, "unknown_field");
+        assert_eq!(payment, card_order().payment);
+    }
+
+    #[test]
+    fn test_localized_messages() {
+        let mut state = FormState::new(invoice(&[("a", 1)]));
+
+        state.set_messages(Rc::new(
+            MessageMap::new()
+                .with("en", "length", "At least {min} characters")
+                .with("fr", "length", "Au moins {min} caractères")
+                .with("fr", "invalid_integer", "« {input} » n'est pas un entier"),
+        ));
+        state.set_locale("en-GB");
+        state.set_value("lines.0.name", "");
+        state.set_value("lines.0.amount", "1.5");
+        assert_eq!(state.field_message("lines.0.name"), "At least 1 characters");
+        assert_eq!(
+            state.field_message("lines.0.amount"),
+            "Enter a whole number"
+        );
+
+        state.set_locale("fr");
+        assert_eq!(state.field_message("lines.0.name"), "Au moins 1 caractères");
+        assert_eq!(
+            state.field_message("lines.0.amount"),
+            "« 1.5 » n'est pas un entier"
+        );
+    }
+
+    #[test]
+    fn test_optional_struct() {
+        let mut state = FormState::new(Shipment { shipping: None });
+        assert_eq!(state.field("shipping.$present").value(), "false");
+        assert!(!state.fields.contains_key("shipping.city"));
+
+        state.set_value("shipping.$present", "true");
+        assert_eq!(
+            state.model().shipping,
+            Some(Address {
+                city: String::new()
+            })
+        );
+        assert_eq!(state.field("shipping.city").value(), "");
+        assert!(!state.validate());
+        assert!(!state.field_valid("shipping.city"));
+
+        state.set_value("shipping.city", "Ghent");
+        assert!(state.field_valid("shipping.city"));
+
+        state.set_value("shipping.$present", "false");
+        assert_eq!(state.model().shipping, None);
+        assert!(!state.fields.contains_key("shipping.city"));
+        assert!(state.validate());
+    }
+
+    #[test]
+    fn test_try_set_value() {
+        let mut state = FormState::new(line("a", 1));
+
+        assert_eq!(
+            state.try_set_value("nmae", "b"),
+            Err(FormError::UnknownField("nmae".into()))
+        );
+        assert_eq!(state.model().try_value("amount.cents"), None);
+        assert_eq!(
+            FormError::from(state.model.set_value("name.first", "b").unwrap_err()),
+            FormError::UnknownField("name.first".into())
+        );
+
+        match state.try_set_value("amount", "x") {
+            Err(FormError::Conversion(e)) => assert_eq!(e.code, "invalid_integer"),
+            result => panic!("Unexpected {:?}", result),
+        }
+        assert_eq!(state.field("amount").value(), "x");
+        assert!(!state.field_valid("amount"));
+
+        state.set_disabled("name", true);
+        assert_eq!(
+            state.try_set_value("name", "b"),
+            Err(FormError::Disabled("name".into()))
+        );
+        assert!(!state.set_value("name", "b"));
+        assert_eq!(state.model().name, "a");
+    }
+
+    #[test]
+    fn test_external_errors() {
+        let mut state = FormState::new(invoice(&[("Tea", 2), ("Coffee", 1)]));
+
+        let unmatched = state.set_errors(
+            [
+                ("lines.0.name", vec!["Sold out"]),
+                ("lines.1.amount", vec!["Too many", "Not in stock"]),
+                ("discount", vec!["Expired"]),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(unmatched.iter().next().unwrap().0, "discount");
+        assert!(!state.valid());
+        assert!(state.field("lines.0.name").external());
+        assert_eq!(state.field("lines.0.name").message(), "Sold out");
+        assert_eq!(state.field("lines.1.amount").message(), "Too many");
+
+        // Local errors come first, the external error stays until the value changes
+        state.set_value("lines.0.name", "");
+        assert!(!state.field("lines.0.name").external());
+        state.set_value("lines.0.name", "Tea");
+        assert!(state.field_valid("lines.0.name"));
+        assert!(state.field("lines.1.amount").external());
+
+        let errors = invoice(&[("", 1)]).validate().unwrap_err();
+        assert!(state.set_errors(errors.into()).is_empty());
+        assert!(state.field_valid("lines.1.amount"));
+        assert!(state.field("lines.0.name").external());
+        assert_eq!(
+            state.field("lines.0.name").validation_error().unwrap().code,
+            "length"
+        );
+
+        // The errors move with their rows and go away with them
+        state.set_errors(
+            [
+                ("lines.0.name", vec!["Sold out"]),
+                ("lines.1.name", vec!["Discontinued"]),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        state.remove("lines", 0).unwrap();
+        assert_eq!(state.field_message("lines.0.name"), "Discontinued");
+        state.push("lines", line("Tea", 2)).unwrap();
+        assert!(state.field_valid("lines.1.name"));
+    }
+}
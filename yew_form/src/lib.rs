@@ -1,13 +1,40 @@
+// Lets the derive's `::yew_form` paths resolve in this crate's own tests
+#[cfg(test)]
+extern crate self as yew_form;
+
+pub mod async_validators;
 pub mod components;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod dates;
+pub mod error;
+pub mod files;
+#[cfg(test)]
+mod fixtures;
 pub mod form;
 pub mod form_field;
 pub mod form_state;
+pub mod messages;
 pub mod model;
+pub mod numbers;
+pub mod paths;
+pub mod persist;
+pub mod snapshot;
+pub mod urlencoded;
 
 pub use components::*;
 
+pub use error::{ConversionError, ExternalErrors, FormError};
+pub use files::{FileRules, FormFile, FormFiles};
 pub use form::{use_form, use_form_with_deps, Form};
-pub use model::{split_field_path, FormValueWrapper, Model};
+pub use messages::{MessageCatalog, MessageMap};
+pub use model::{
+    join_field_path, split_field_path, FieldMeta, FormEnum, FormList, FormMap, FormValueWrapper,
+    Model, ValueKind,
+};
+pub use numbers::NumberFormat;
+pub use paths::{FieldName, FieldPath, FieldPaths};
+pub use persist::{use_form_persisted, use_form_persisted_with, DraftStorage};
+pub use snapshot::FormSnapshot;
 
 #[cfg(feature = "derive")]
-pub use yew_form_derive::Model;
+pub use yew_form_derive::{FormValue, Model};
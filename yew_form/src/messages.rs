@@ -0,0 +1,160 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde_json::Value;
+use validator::ValidationError;
+use yew::AttrValue;
+
+use crate::error::ConversionError;
+
+pub type MessageParams = HashMap<Cow<'static, str>, Value>;
+
+/// Resolves the messages shown for invalid fields.
+///
+/// Validation errors are looked up by the `message` of the validator attribute first, when set,
+/// then by their `code`. Conversion errors are looked up by their [`code`](ConversionError::code)
+/// with `expected`, `input` and `path` as params.
+/// The texts shown by the components are looked up by their key without params, e.g. `dropzone_hint`.
+pub trait MessageCatalog {
+    /// Returns the message for `key` in `locale`, or [`None`] to fall back to the built-in message
+    fn message(&self, locale: &str, key: &str, params: &MessageParams) -> Option<String>;
+}
+
+/// A [`MessageCatalog`] backed by a map of templates per locale.
+///
+/// Params are interpolated where the template contains their name between braces, e.g. `{min}`.
+/// When a locale like `fr-CA` has no template, the language `fr` is tried.
+///
+/// ```
+/// # use yew_form::messages::MessageMap;
+/// let messages = MessageMap::new()
+///     .with("en", "length", "Enter between {min} and {max} characters")
+///     .with("fr", "length", "Saisissez entre {min} et {max} caractères");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MessageMap {
+    templates: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageMap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with(
+        mut self,
+        locale: impl Into<String>,
+        key: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Self {
+        self.insert(locale, key, template);
+        self
+    }
+
+    pub fn insert(
+        &mut self,
+        locale: impl Into<String>,
+        key: impl Into<String>,
+        template: impl Into<String>,
+    ) {
+        self.templates
+            .entry(locale.into())
+            .or_default()
+            .insert(key.into(), template.into());
+    }
+
+    fn template(&self, locale: &str, key: &str) -> Option<&String> {
+        let language = locale.split('-').next().unwrap_or(locale);
+
+        [locale, language]
+            .into_iter()
+            .find_map(|locale| self.templates.get(locale)?.get(key))
+    }
+}
+
+impl MessageCatalog for MessageMap {
+    fn message(&self, locale: &str, key: &str, params: &MessageParams) -> Option<String> {
+        let mut message = self.template(locale, key)?.clone();
+
+        for (name, value) in params {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+
+            message = message.replace(&format!("{{{}}}", name), &value);
+        }
+
+        Some(message)
+    }
+}
+
+/// The catalog and locale messages of a form are resolved with
+#[derive(Clone, Default)]
+pub(crate) struct Messages {
+    catalog: Option<Rc<dyn MessageCatalog>>,
+    locale: AttrValue,
+}
+
+impl PartialEq for Messages {
+    fn eq(&self, other: &Self) -> bool {
+        self.locale == other.locale
+            && match (&self.catalog, &other.catalog) {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+impl Messages {
+    pub(crate) fn set_catalog(&mut self, catalog: Rc<dyn MessageCatalog>) {
+        self.catalog = Some(catalog);
+    }
+
+    pub(crate) fn set_locale(&mut self, locale: AttrValue) {
+        self.locale = locale;
+    }
+
+    pub(crate) fn locale(&self) -> &AttrValue {
+        &self.locale
+    }
+
+    fn lookup(&self, key: &str, params: &MessageParams) -> Option<AttrValue> {
+        self.catalog
+            .as_ref()?
+            .message(&self.locale, key, params)
+            .map(AttrValue::from)
+    }
+
+    pub(crate) fn validation(&self, error: &ValidationError) -> AttrValue {
+        error
+            .message
+            .as_ref()
+            .and_then(|message| self.lookup(message, &error.params))
+            .or_else(|| self.lookup(&error.code, &error.params))
+            .unwrap_or_else(|| match &error.message {
+                Some(Cow::Borrowed(message)) => (*message).into(),
+                Some(Cow::Owned(message)) => message.to_owned().into(),
+                None => "Error".into(),
+            })
+    }
+
+    /// The text shown by a component for `key`, or `default` if the catalog has none
+    pub(crate) fn text(&self, key: &str, default: &'static str) -> AttrValue {
+        self.lookup(key, &MessageParams::new())
+            .unwrap_or(AttrValue::Static(default))
+    }
+
+    pub(crate) fn conversion(&self, error: &ConversionError) -> AttrValue {
+        let params = MessageParams::from([
+            ("expected".into(), error.expected.into()),
+            ("input".into(), error.input.as_str().into()),
+            ("path".into(), error.path.as_str().into()),
+        ]);
+
+        self.lookup(&error.code, &params)
+            .unwrap_or_else(|| error.to_string().into())
+    }
+}
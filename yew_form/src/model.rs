@@ -1,22 +1,255 @@
 use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
     fmt::{Debug, Display},
+    hash::{BuildHasher, Hash},
     ops::{Deref, DerefMut},
+    rc::Rc,
     str::FromStr,
 };
-use strum::Display;
 use validator::Validate;
 use yew::AttrValue;
 
+use crate::error::{ConversionError, FormError};
+use crate::files::FormFiles;
+
+/// The path segment that selects the variant of an enum bound as a sub-form
+pub const VARIANT_FIELD: &str = "$variant";
+
+/// The path segment that toggles an optional sub-form, e.g. `shipping.$present` for an `Option<Address>`
+pub const PRESENT_FIELD: &str = "$present";
+
 pub trait FormValue {
     fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
         // By default, announce the value to be a scalar
         fields.push(prefix.to_owned().into());
     }
-    fn value(&self, field_path: &str) -> AttrValue;
-    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), &'static str>;
+    /// The value at `field_path`, panics if there is no such path
+    fn value(&self, field_path: &str) -> AttrValue {
+        self.try_value(field_path)
+            .unwrap_or_else(|| panic!("Field {} does not exist", field_path))
+    }
+
+    /// The value at `field_path`, or [`None`] if there is no such path
+    fn try_value(&self, field_path: &str) -> Option<AttrValue>;
+
+    /// Fails with the code `unknown_field` if there is no such path, see [`ConversionError`]
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError>;
+
+    /// Returns `true` if `field_name` is the first segment of one of the paths of this value.
+    /// Used to resolve the paths of `#[form(flatten)]` fields.
+    fn has_field(&self, field_name: &str) -> bool {
+        let _ = field_name;
+        false
+    }
+
+    /// Returns the list at `field_path`, if the value at that path is a collection.
+    /// Used by [`Form`](crate::Form) to add, remove and reorder rows.
+    fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+        let _ = field_path;
+        None
+    }
+
+    /// Returns the map at `field_path`, if the value at that path is a keyed collection.
+    /// Used by [`Form`](crate::Form) to add, rename and remove keys.
+    fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+        let _ = field_path;
+        None
+    }
+
+    /// Returns the files at `field_path`, if the value at that path is a [`FormFiles`]
+    fn files(&self, field_path: &str) -> Option<&FormFiles> {
+        let _ = field_path;
+        None
+    }
+
+    /// Returns the files at `field_path`, if the value at that path is a [`FormFiles`].
+    /// Used by [`Form`](crate::Form) to set the files selected in a file input.
+    fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+        let _ = field_path;
+        None
+    }
+
+    /// The kind of the value at `field_path`, or [`None`] if there is no such path.
+    /// Decides how a field is displayed, e.g. whether a number format applies.
+    fn kind(field_path: &str) -> Option<ValueKind>
+    where
+        Self: Sized,
+    {
+        let _ = field_path;
+        Some(ValueKind::Text)
+    }
+
+    /// What is known about the value at `field_path` from its type and attributes,
+    /// or [`None`] if there is no such path
+    fn field_meta(field_path: &str) -> Option<FieldMeta>
+    where
+        Self: Sized,
+    {
+        Self::kind(field_path).map(FieldMeta::new)
+    }
+
+    /// Creates a value from the input of its field, used to fill in an empty `Option`.
+    /// Values bound as a single field convert the input like [`set_value`](FormValue::set_value),
+    /// the others fail with [`ConversionError::invalid`].
+    fn parse_value(value: &str) -> Result<Self, ConversionError>
+    where
+        Self: Sized;
+
+    /// Creates the value a sub-form starts with when an `Option` of it is turned on
+    /// through its `$present` path, [`None`] if there is no such value
+    fn new_value() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// The kinds of values a field can hold
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ValueKind {
+    Text,
+    Bool,
+    Integer,
+    /// A number with decimals, such as a float or a decimal
+    Number,
+    Date,
+    Time,
+    DateTime,
+    Month,
+    Week,
+    /// One of a fixed set of options, see [`FormEnum`]
+    Options,
+    /// Files picked by the user, see [`FormFiles`](crate::FormFiles)
+    Files,
 }
 
-pub trait Model: FormValue + Validate + PartialEq + Clone + 'static {}
+/// The metadata of a field, generated from the `#[validate(...)]` and `#[form(label, help)]`
+/// attributes by the derive. Used by the components to set the matching HTML attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMeta {
+    pub kind: ValueKind,
+    /// The field can be left empty, e.g. an `Option`
+    pub optional: bool,
+    /// The field has to be filled in for the form to be valid
+    pub required: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_length: Option<u64>,
+    pub max_length: Option<u64>,
+    /// The `pattern` attribute of the input, see [`html_pattern`](Self::html_pattern)
+    pub pattern: Option<AttrValue>,
+    pub label: Option<AttrValue>,
+    pub help: Option<AttrValue>,
+    /// The values that can be chosen from, for fields of the kind [`ValueKind::Options`]
+    pub options: &'static [&'static str],
+}
+
+impl FieldMeta {
+    /// Values other than text, booleans and files can't be converted from an empty input, so they are required
+    pub fn new(kind: ValueKind) -> Self {
+        FieldMeta {
+            kind,
+            optional: false,
+            required: !matches!(kind, ValueKind::Text | ValueKind::Bool | ValueKind::Files),
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+            label: None,
+            help: None,
+            options: &[],
+        }
+    }
+
+    /// The `pattern` attribute matching the same inputs as the Rust `regex`, if there is one.
+    ///
+    /// A `pattern` has to match the whole input and uses the syntax of JavaScript, while a `regex`
+    /// of `validator` matches anywhere in the input. So only a regex anchored with `^` and `$`,
+    /// without alternatives or `(?` groups whose syntax differs, is given as a pattern.
+    pub fn html_pattern(regex: &str) -> Option<AttrValue> {
+        let inner = regex.strip_prefix('^')?.strip_suffix('$')?;
+        let escaped = inner.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1;
+
+        (!escaped && !inner.contains('|') && !inner.contains("(?")).then(|| inner.to_owned().into())
+    }
+}
+
+/// A list of values whose rows can be added, removed and reordered at runtime
+pub trait FormList {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `value` at `index`, the value is given back if it has the wrong type
+    fn insert(&mut self, index: usize, value: Box<dyn Any>) -> Result<(), Box<dyn Any>>;
+
+    fn remove(&mut self, index: usize);
+
+    fn swap(&mut self, a: usize, b: usize);
+
+    /// Appends a new value, returns `false` if the values have no [`new_value`](FormValue::new_value)
+    fn push_new(&mut self) -> bool {
+        false
+    }
+}
+
+/// A map of values whose keys can be added, renamed and removed at runtime
+pub trait FormMap {
+    /// The keys of the map, in the order their fields are announced
+    fn keys(&self) -> Vec<String>;
+
+    /// Inserts `value` under `key`, the value is given back if it has the wrong type
+    /// or the key can't be converted
+    fn insert(&mut self, key: &str, value: Box<dyn Any>) -> Result<(), Box<dyn Any>>;
+
+    /// Returns `false` if `from` does not exist
+    fn rename(&mut self, from: &str, to: &str) -> bool;
+
+    /// Returns `false` if `key` does not exist
+    fn remove(&mut self, key: &str) -> bool;
+
+    /// Inserts a new value under `key` if it doesn't exist, returns `false` if the key can't be
+    /// converted or the values have no [`new_value`](FormValue::new_value)
+    fn insert_new(&mut self, key: &str) -> bool {
+        let _ = key;
+        false
+    }
+}
+
+/// A value that is one of a fixed set of options, such as an enum derived with `#[derive(FormValue)]`
+pub trait FormEnum: FormValue {
+    /// The values of all the options, in declaration order
+    fn variants() -> &'static [&'static str];
+}
+
+pub trait Model: FormValue + Validate + PartialEq + Clone + 'static {
+    /// Encodes the fields as `application/x-www-form-urlencoded`, with their path as key
+    fn to_urlencoded(&self) -> String {
+        crate::urlencoded::encode(self)
+    }
+
+    /// Decodes `application/x-www-form-urlencoded` data into a [`new_value`](FormValue::new_value),
+    /// see the [`urlencoded`](crate::urlencoded) module. Fails with [`FormError::NoNewValue`]
+    /// if there is none, [`with_urlencoded`](Self::with_urlencoded) starts from a given value instead.
+    fn from_urlencoded(query: &str) -> Result<Self, Vec<FormError>> {
+        match Self::new_value() {
+            Some(model) => model.with_urlencoded(query),
+            None => Err(vec![FormError::NoNewValue(std::any::type_name::<Self>())]),
+        }
+    }
+
+    /// Decodes `application/x-www-form-urlencoded` data into `self`,
+    /// the fields missing from the data keep their value and rows are added after the existing ones
+    fn with_urlencoded(self, query: &str) -> Result<Self, Vec<FormError>> {
+        crate::urlencoded::decode(self, query)
+    }
+}
 
 pub fn split_field_path(field_path: &str) -> (&str, &str) {
     if let Some(index) = field_path.find(".") {
@@ -26,33 +259,64 @@ pub fn split_field_path(field_path: &str) -> (&str, &str) {
     }
 }
 
+/// Returns `true` if `field_path` matches `pattern`, where a `*` segment matches any single segment
+pub(crate) fn matches_field_pattern(pattern: &str, field_path: &str) -> bool {
+    pattern.split('.').count() == field_path.split('.').count()
+        && pattern
+            .split('.')
+            .zip(field_path.split('.'))
+            .all(|(p, s)| p == "*" || p == s)
+}
+
+pub fn join_field_path(prefix: &str, field_name: &str) -> String {
+    if prefix.is_empty() {
+        field_name.to_owned()
+    } else if field_name.is_empty() {
+        prefix.to_owned()
+    } else {
+        format!("{}.{}", prefix, field_name)
+    }
+}
+
 macro_rules! impl_form_value {
-    ($($t:ty),+) => {
+    ($kind:ident: $($t:ty),+) => {
         $(
             impl FormValue for $t {
-                fn value(&self, field_path: &str) -> AttrValue {
-                    debug_assert!(field_path == "");
-                    self.to_string().into()
+                fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+                    field_path.is_empty().then(|| self.to_string().into())
                 }
 
-                fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), &'static str> {
-                    debug_assert!(field_path == "");
-
-                    if let Ok(v) = value.parse::<$t>() {
-                        *self = v;
-                        Ok(())
-                    } else {
-                        Err("Could not convert")
+                fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+                    if !field_path.is_empty() {
+                        return Err(ConversionError::unknown_field::<Self>(field_path, value));
                     }
+
+                    *self = Self::parse_value(value)?;
+                    Ok(())
+                }
+
+                fn kind(field_path: &str) -> Option<ValueKind> {
+                    field_path.is_empty().then_some(ValueKind::$kind)
+                }
+
+                fn parse_value(value: &str) -> Result<Self, ConversionError> {
+                    value
+                        .parse::<$t>()
+                        .map_err(|e| ConversionError::parse::<$t, _>(value, Some(&e)))
+                }
+
+                fn new_value() -> Option<Self> {
+                    Some(Default::default())
                 }
             }
         )+
     };
 }
 
-impl_form_value!(
-    bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, String
-);
+impl_form_value!(Bool: bool);
+impl_form_value!(Integer: u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_form_value!(Number: f32, f64);
+impl_form_value!(Text: String);
 
 #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FormValueWrapper<T>(pub T);
@@ -70,34 +334,48 @@ impl<T> FormValue for FormValueWrapper<T>
 where
     T: ToString + FromStr,
 {
-    fn value(&self, field_path: &str) -> AttrValue {
-        debug_assert!(field_path == "");
-        self.0.to_string().into()
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        field_path.is_empty().then(|| self.0.to_string().into())
     }
 
-    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), &'static str> {
-        debug_assert!(field_path == "");
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        if !field_path.is_empty() {
+            return Err(ConversionError::unknown_field::<Self>(field_path, value));
+        }
 
-        if let Ok(v) = value.parse::<T>() {
-            self.0 = v;
-            Ok(())
-        } else {
-            Err("Could not convert")
+        *self = Self::parse_value(value)?;
+        Ok(())
+    }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        match value.parse::<T>() {
+            Ok(v) => Ok(FormValueWrapper(v)),
+            Err(_) => Err(ConversionError::invalid::<T>(value)),
         }
     }
 }
 
 impl FormValue for AttrValue {
-    fn value(&self, field_path: &str) -> AttrValue {
-        debug_assert!(field_path == "");
-        self.clone()
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        field_path.is_empty().then(|| self.clone())
     }
 
-    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), &'static str> {
-        debug_assert!(field_path == "");
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        if !field_path.is_empty() {
+            return Err(ConversionError::unknown_field::<Self>(field_path, value));
+        }
+
         *self = value.to_string().into();
         Ok(())
     }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        Ok(value.to_owned().into())
+    }
+
+    fn new_value() -> Option<Self> {
+        Some(Default::default())
+    }
 }
 
 impl<T> Deref for FormValueWrapper<T> {
@@ -120,35 +398,652 @@ impl<T> From<T> for FormValueWrapper<T> {
     }
 }
 
+/// An `Option` of a value bound as a single field is empty when its input is empty.
+/// An `Option` of a sub-form is toggled through its `$present` path instead,
+/// the fields of the sub-form only exist while it is present.
 impl<T> FormValue for Option<T>
 where
-    T: FormValue + ToString + FromStr,
+    T: FormValue,
 {
-    fn value(&self, field_path: &str) -> AttrValue {
+    fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
+        if T::kind("").is_some() {
+            fields.push(prefix.to_owned().into());
+            return;
+        }
+
+        fields.push(join_field_path(prefix, PRESENT_FIELD).into());
+
+        if let Some(value) = self {
+            value.fields(prefix, fields);
+        }
+    }
+
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        if field_path == PRESENT_FIELD {
+            return Some(self.is_some().to_string().into());
+        }
+
         match self {
-            Some(value) => value.value(field_path),
-            None => Default::default(),
+            Some(value) => value.try_value(field_path),
+            None if field_path.is_empty() => Some(Default::default()),
+            None => None,
         }
     }
 
-    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), &'static str> {
-        debug_assert!(field_path == "");
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        if field_path == PRESENT_FIELD {
+            let present = bool::parse_value(value)?;
 
-        if value.is_empty() {
-            *self = None;
-            Ok(())
-        } else if let Ok(v) = value.parse::<T>() {
-            *self = Some(v);
-            Ok(())
-        } else {
-            Err("Could not convert")
+            if !present {
+                *self = None;
+            } else if self.is_none() {
+                *self = Some(T::new_value().ok_or_else(|| ConversionError::invalid::<T>(value))?);
+            }
+
+            return Ok(());
+        }
+
+        if field_path.is_empty() {
+            *self = Self::parse_value(value)?;
+            return Ok(());
+        }
+
+        match self {
+            Some(v) => v.set_value(field_path, value),
+            None => Err(ConversionError::unknown_field::<Self>(field_path, value)),
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        T::kind("").is_none()
+            && (field_name == PRESENT_FIELD
+                || matches!(self, Some(value) if value.has_field(field_name)))
+    }
+
+    fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+        self.as_mut()?.list_mut(field_path)
+    }
+
+    fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+        self.as_mut()?.map_mut(field_path)
+    }
+
+    fn files(&self, field_path: &str) -> Option<&FormFiles> {
+        self.as_ref()?.files(field_path)
+    }
+
+    fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+        self.as_mut()?.files_mut(field_path)
+    }
+
+    fn kind(field_path: &str) -> Option<ValueKind> {
+        match field_path {
+            PRESENT_FIELD => Some(ValueKind::Bool),
+            _ => T::kind(field_path),
+        }
+    }
+
+    fn field_meta(field_path: &str) -> Option<FieldMeta> {
+        match field_path {
+            PRESENT_FIELD => Some(FieldMeta::new(ValueKind::Bool)),
+            // Only the option itself can be left empty, not the fields of a present sub-form
+            "" => T::field_meta(field_path).map(|meta| FieldMeta {
+                optional: true,
+                required: false,
+                ..meta
+            }),
+            _ => T::field_meta(field_path),
+        }
+    }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        match value {
+            "" => Ok(None),
+            _ => T::parse_value(value).map(Some),
+        }
+    }
+
+    fn new_value() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T> FormValue for Vec<T>
+where
+    T: FormValue + 'static,
+{
+    fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
+        for (index, value) in self.iter().enumerate() {
+            value.fields(&join_field_path(prefix, &index.to_string()), fields);
+        }
+    }
+
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get(index.parse::<usize>().ok()?)?.try_value(suffix)
+    }
+
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        let (index, suffix) = split_field_path(field_path);
+
+        match index.parse::<usize>().ok().and_then(|i| self.get_mut(i)) {
+            Some(v) => v.set_value(suffix, value).map_err(|e| e.prefixed(index)),
+            None => Err(ConversionError::unknown_field::<Self>(field_path, value)),
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        matches!(field_name.parse::<usize>(), Ok(index) if index < self.len())
+    }
+
+    fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+        if field_path.is_empty() {
+            return Some(self);
+        }
+
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get_mut(index.parse::<usize>().ok()?)?.list_mut(suffix)
+    }
+
+    fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get_mut(index.parse::<usize>().ok()?)?.map_mut(suffix)
+    }
+
+    fn files(&self, field_path: &str) -> Option<&FormFiles> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get(index.parse::<usize>().ok()?)?.files(suffix)
+    }
+
+    fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get_mut(index.parse::<usize>().ok()?)?
+            .files_mut(suffix)
+    }
+
+    fn kind(field_path: &str) -> Option<ValueKind> {
+        let (index, suffix) = split_field_path(field_path);
+
+        index.parse::<usize>().ok().and(T::kind(suffix))
+    }
+
+    fn field_meta(field_path: &str) -> Option<FieldMeta> {
+        let (index, suffix) = split_field_path(field_path);
+
+        index.parse::<usize>().ok().and(T::field_meta(suffix))
+    }
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        Err(ConversionError::invalid::<Self>(value))
+    }
+
+    fn new_value() -> Option<Self> {
+        Some(Vec::new())
+    }
+}
+
+impl<T> FormList for Vec<T>
+where
+    T: FormValue + 'static,
+{
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn insert(&mut self, index: usize, value: Box<dyn Any>) -> Result<(), Box<dyn Any>> {
+        Vec::insert(self, index, *value.downcast::<T>()?);
+        Ok(())
+    }
+
+    fn remove(&mut self, index: usize) {
+        Vec::remove(self, index);
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        <[T]>::swap(self, a, b);
+    }
+
+    fn push_new(&mut self) -> bool {
+        T::new_value().map(|value| self.push(value)).is_some()
+    }
+}
+
+/// Implements [`FormValue`] and [`FormMap`] for a map type, announcing a path per key.
+/// Keys can't contain a `.` as it is used as the path separator.
+macro_rules! impl_form_map {
+    ($map:ident<K, T $(, $s:ident)?> where K: $($bound:path),+) => {
+        impl<K, T $(, $s)?> FormValue for $map<K, T $(, $s)?>
+        where
+            K: Display + FromStr $(+ $bound)+,
+            T: FormValue + 'static,
+            $($s: BuildHasher + Default,)?
+        {
+            fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
+                let mut entries: Vec<(String, &T)> =
+                    self.iter().map(|(k, v)| (k.to_string(), v)).collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+                for (key, value) in entries {
+                    value.fields(&join_field_path(prefix, &key), fields);
+                }
+            }
+
+            fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+                let (key, suffix) = split_field_path(field_path);
+
+                self.get(&key.parse::<K>().ok()?)?.try_value(suffix)
+            }
+
+            fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+                let (key, suffix) = split_field_path(field_path);
+
+                match key.parse::<K>().ok().and_then(|k| self.get_mut(&k)) {
+                    Some(v) => v.set_value(suffix, value).map_err(|e| e.prefixed(key)),
+                    None => Err(ConversionError::unknown_field::<Self>(field_path, value)),
+                }
+            }
+
+            fn has_field(&self, field_name: &str) -> bool {
+                matches!(field_name.parse::<K>(), Ok(key) if self.contains_key(&key))
+            }
+
+            fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+                let (key, suffix) = split_field_path(field_path);
+
+                self.get_mut(&key.parse::<K>().ok()?)?.list_mut(suffix)
+            }
+
+            fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+                if field_path.is_empty() {
+                    return Some(self);
+                }
+
+                let (key, suffix) = split_field_path(field_path);
+
+                self.get_mut(&key.parse::<K>().ok()?)?.map_mut(suffix)
+            }
+
+            fn files(&self, field_path: &str) -> Option<&FormFiles> {
+                let (key, suffix) = split_field_path(field_path);
+
+                self.get(&key.parse::<K>().ok()?)?.files(suffix)
+            }
+
+            fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+                let (key, suffix) = split_field_path(field_path);
+
+                self.get_mut(&key.parse::<K>().ok()?)?.files_mut(suffix)
+            }
+
+            fn kind(field_path: &str) -> Option<ValueKind> {
+                let (key, suffix) = split_field_path(field_path);
+
+                key.parse::<K>().ok().and(T::kind(suffix))
+            }
+
+            fn field_meta(field_path: &str) -> Option<FieldMeta> {
+                let (key, suffix) = split_field_path(field_path);
+
+                key.parse::<K>().ok().and(T::field_meta(suffix))
+            }
+
+            fn parse_value(value: &str) -> Result<Self, ConversionError> {
+                Err(ConversionError::invalid::<Self>(value))
+            }
+
+            fn new_value() -> Option<Self> {
+                Some(Default::default())
+            }
+        }
+
+        impl<K, T $(, $s)?> FormMap for $map<K, T $(, $s)?>
+        where
+            K: Display + FromStr $(+ $bound)+,
+            T: FormValue + 'static,
+            $($s: BuildHasher + Default,)?
+        {
+            fn keys(&self) -> Vec<String> {
+                let mut keys: Vec<String> = $map::keys(self).map(ToString::to_string).collect();
+                keys.sort();
+                keys
+            }
+
+            fn insert(&mut self, key: &str, value: Box<dyn Any>) -> Result<(), Box<dyn Any>> {
+                let value = value.downcast::<T>()?;
+
+                match key.parse::<K>() {
+                    Ok(key) => {
+                        $map::insert(self, key, *value);
+                        Ok(())
+                    }
+                    Err(_) => Err(value),
+                }
+            }
+
+            fn rename(&mut self, from: &str, to: &str) -> bool {
+                match (from.parse::<K>(), to.parse::<K>()) {
+                    (Ok(from), Ok(to)) => match $map::remove(self, &from) {
+                        Some(value) => {
+                            $map::insert(self, to, value);
+                            true
+                        }
+                        None => false,
+                    },
+                    _ => false,
+                }
+            }
+
+            fn remove(&mut self, key: &str) -> bool {
+                matches!(key.parse::<K>(), Ok(key) if $map::remove(self, &key).is_some())
+            }
+
+            fn insert_new(&mut self, key: &str) -> bool {
+                match (key.parse::<K>(), T::new_value()) {
+                    (Ok(key), _) if self.contains_key(&key) => true,
+                    (Ok(key), Some(value)) => {
+                        $map::insert(self, key, value);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        }
+    };
+}
+
+impl_form_map!(HashMap<K, T, S> where K: Eq, Hash);
+impl_form_map!(BTreeMap<K, T> where K: Ord);
+
+impl<T, const N: usize> FormValue for [T; N]
+where
+    T: FormValue,
+{
+    fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
+        for (index, value) in self.iter().enumerate() {
+            value.fields(&join_field_path(prefix, &index.to_string()), fields);
+        }
+    }
+
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get(index.parse::<usize>().ok()?)?.try_value(suffix)
+    }
+
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        let (index, suffix) = split_field_path(field_path);
+
+        match index.parse::<usize>().ok().and_then(|i| self.get_mut(i)) {
+            Some(v) => v.set_value(suffix, value).map_err(|e| e.prefixed(index)),
+            None => Err(ConversionError::unknown_field::<Self>(field_path, value)),
         }
     }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        matches!(field_name.parse::<usize>(), Ok(index) if index < N)
+    }
+
+    fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get_mut(index.parse::<usize>().ok()?)?.list_mut(suffix)
+    }
+
+    fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get_mut(index.parse::<usize>().ok()?)?.map_mut(suffix)
+    }
+
+    fn files(&self, field_path: &str) -> Option<&FormFiles> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get(index.parse::<usize>().ok()?)?.files(suffix)
+    }
+
+    fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+        let (index, suffix) = split_field_path(field_path);
+
+        self.get_mut(index.parse::<usize>().ok()?)?
+            .files_mut(suffix)
+    }
+
+    fn kind(field_path: &str) -> Option<ValueKind> {
+        let (index, suffix) = split_field_path(field_path);
+
+        index.parse::<usize>().ok().filter(|i| *i < N)?;
+        T::kind(suffix)
+    }
+
+    fn field_meta(field_path: &str) -> Option<FieldMeta> {
+        let (index, suffix) = split_field_path(field_path);
+
+        index.parse::<usize>().ok().filter(|i| *i < N)?;
+        T::field_meta(suffix)
+    }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        Err(ConversionError::invalid::<Self>(value))
+    }
+
+    fn new_value() -> Option<Self> {
+        let values: Option<Vec<T>> = (0..N).map(|_| T::new_value()).collect();
+
+        values?.try_into().ok()
+    }
+}
+
+/// Implements [`FormValue`] for a tuple, every element is announced under its index
+macro_rules! impl_form_value_tuple {
+    ($($index:tt: $t:ident),+) => {
+        impl<$($t: FormValue),+> FormValue for ($($t,)+) {
+            fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
+                $(
+                self.$index.fields(&join_field_path(prefix, stringify!($index)), fields);
+                )+
+            }
+
+            fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => self.$index.try_value(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => self.$index.set_value(suffix, value).map_err(|e| e.prefixed(index)),)+
+                    _ => Err(ConversionError::unknown_field::<Self>(field_path, value)),
+                }
+            }
+
+            fn has_field(&self, field_name: &str) -> bool {
+                [$(stringify!($index)),+].contains(&field_name)
+            }
+
+            fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => self.$index.list_mut(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => self.$index.map_mut(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn files(&self, field_path: &str) -> Option<&FormFiles> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => self.$index.files(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => self.$index.files_mut(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn kind(field_path: &str) -> Option<ValueKind> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => $t::kind(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn field_meta(field_path: &str) -> Option<FieldMeta> {
+                let (index, suffix) = split_field_path(field_path);
+
+                match index {
+                    $(stringify!($index) => $t::field_meta(suffix),)+
+                    _ => None,
+                }
+            }
+
+            fn parse_value(value: &str) -> Result<Self, ConversionError> {
+                Err(ConversionError::invalid::<Self>(value))
+            }
+
+            fn new_value() -> Option<Self> {
+                Some(($($t::new_value()?,)+))
+            }
+        }
+    };
+}
+
+impl_form_value_tuple!(0: A);
+impl_form_value_tuple!(0: A, 1: B);
+impl_form_value_tuple!(0: A, 1: B, 2: C);
+impl_form_value_tuple!(0: A, 1: B, 2: C, 3: D);
+impl_form_value_tuple!(0: A, 1: B, 2: C, 3: D, 4: E);
+impl_form_value_tuple!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F);
+impl_form_value_tuple!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G);
+impl_form_value_tuple!(0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H);
+
+/// Implements [`FormValue`] for a pointer by forwarding to the value it points to,
+/// `$get_mut` gives mutable access to that value
+macro_rules! impl_form_value_pointer {
+    ($pointer:ident<T> where $($bound:path),+; |$this:ident| $get_mut:expr) => {
+        impl<T> FormValue for $pointer<T>
+        where
+            T: FormValue $(+ $bound)+,
+        {
+            fn fields(&self, prefix: &str, fields: &mut Vec<AttrValue>) {
+                (**self).fields(prefix, fields)
+            }
+
+            fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+                (**self).try_value(field_path)
+            }
+
+            fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+                let $this = self;
+                $get_mut.set_value(field_path, value)
+            }
+
+            fn has_field(&self, field_name: &str) -> bool {
+                (**self).has_field(field_name)
+            }
+
+            fn list_mut(&mut self, field_path: &str) -> Option<&mut dyn FormList> {
+                let $this = self;
+                $get_mut.list_mut(field_path)
+            }
+
+            fn map_mut(&mut self, field_path: &str) -> Option<&mut dyn FormMap> {
+                let $this = self;
+                $get_mut.map_mut(field_path)
+            }
+
+            fn files(&self, field_path: &str) -> Option<&FormFiles> {
+                (**self).files(field_path)
+            }
+
+            fn files_mut(&mut self, field_path: &str) -> Option<&mut FormFiles> {
+                let $this = self;
+                $get_mut.files_mut(field_path)
+            }
+
+            fn kind(field_path: &str) -> Option<ValueKind> {
+                T::kind(field_path)
+            }
+
+            fn field_meta(field_path: &str) -> Option<FieldMeta> {
+                T::field_meta(field_path)
+            }
+
+            fn parse_value(value: &str) -> Result<Self, ConversionError> {
+                T::parse_value(value).map($pointer::new)
+            }
+
+            fn new_value() -> Option<Self> {
+                T::new_value().map($pointer::new)
+            }
+        }
+    };
+}
+
+impl_form_value_pointer!(Box<T> where Sized; |this| &mut **this);
+// Values shared with other owners are cloned before they are changed
+impl_form_value_pointer!(Rc<T> where Clone; |this| Rc::make_mut(this));
+
+impl FormValue for Cow<'static, str> {
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        field_path.is_empty().then(|| match self {
+            Cow::Borrowed(value) => AttrValue::Static(value),
+            Cow::Owned(value) => value.clone().into(),
+        })
+    }
+
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        if !field_path.is_empty() {
+            return Err(ConversionError::unknown_field::<Self>(field_path, value));
+        }
+
+        *self = Cow::Owned(value.to_owned());
+        Ok(())
+    }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        Ok(Cow::Owned(value.to_owned()))
+    }
+
+    fn new_value() -> Option<Self> {
+        Some(Cow::Borrowed(""))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::model::split_field_path;
+    use yew::AttrValue;
+
+    use crate::error::ConversionError;
+    use crate::model::{
+        split_field_path, FieldMeta, FormEnum, FormValue, FormValueWrapper, ValueKind,
+    };
 
     #[test]
     fn test_split_field_path() {
@@ -170,4 +1065,385 @@ mod tests {
         assert_eq!(field, "field");
         assert_eq!(suffix, "sub.subsub");
     }
+
+    #[test]
+    fn test_vec_fields() {
+        let mut list = vec![vec![1u32, 2], vec![3]];
+        let mut fields = vec![];
+
+        list.fields("list", &mut fields);
+
+        assert_eq!(fields, vec!["list.0.0", "list.0.1", "list.1.0"]);
+        assert_eq!(list.value("1.0"), "3");
+
+        list.set_value("0.1", "5").unwrap();
+        assert_eq!(list[0][1], 5);
+
+        let rows = list.list_mut("0").unwrap();
+        rows.insert(0, Box::new(7u32)).unwrap();
+        assert!(rows.insert(0, Box::new("7")).is_err());
+        assert_eq!(list[0], vec![7, 1, 5]);
+    }
+
+    #[derive(crate::FormValue, Debug, PartialEq)]
+    enum Status {
+        Draft,
+        #[form(rename = "sent")]
+        Sent,
+    }
+
+    #[test]
+    fn test_enum_value() {
+        let mut status = Status::Draft;
+
+        assert_eq!(Status::variants(), ["Draft", "sent"]);
+        assert_eq!(status.value(""), "Draft");
+
+        status.set_value("", "sent").unwrap();
+        assert_eq!(status, Status::Sent);
+        assert!(status.set_value("", "Sent").is_err());
+        assert_eq!(status.try_value("sent"), None);
+        assert_eq!(
+            status.set_value("sent", "Draft").unwrap_err().code,
+            "unknown_field"
+        );
+    }
+
+    #[derive(crate::FormValue, Debug, PartialEq)]
+    enum Delivery {
+        Pickup,
+        Courier {
+            address: crate::fixtures::Address,
+            #[form(skip)]
+            attempts: u8,
+        },
+    }
+
+    #[test]
+    fn test_variant_new_value() {
+        let mut delivery = Delivery::new_value().unwrap();
+        assert_eq!(delivery, Delivery::Pickup);
+
+        // The address has no default, the variant is built from its new value
+        delivery.set_value("$variant", "Courier").unwrap();
+        assert_eq!(
+            delivery,
+            Delivery::Courier {
+                address: crate::fixtures::Address::new_value().unwrap(),
+                attempts: 0
+            }
+        );
+        assert_eq!(delivery.value("address.city"), "");
+    }
+
+    #[derive(crate::FormValue, Debug, PartialEq)]
+    #[form(transparent)]
+    struct Email(String);
+
+    #[derive(crate::FormValue, Debug, Default, PartialEq)]
+    struct Point(f64, f64);
+
+    #[derive(crate::FormValue)]
+    struct Place {
+        email: Email,
+        location: Point,
+    }
+
+    #[test]
+    fn test_tuple_struct_value() {
+        let mut place = Place {
+            email: Email("a@b.c".into()),
+            location: Point(1.0, 2.0),
+        };
+        let mut fields = vec![];
+
+        place.fields("", &mut fields);
+        assert_eq!(fields, vec!["email", "location.0", "location.1"]);
+        assert_eq!(place.value("email"), "a@b.c");
+
+        place.set_value("location.1", "2.5").unwrap();
+        place.set_value("email", "d@e.f").unwrap();
+        assert_eq!(place.location, Point(1.0, 2.5));
+        assert_eq!(place.email, Email("d@e.f".into()));
+    }
+
+    #[derive(crate::FormValue, Default)]
+    struct Contact {
+        #[form(rename = "postalCode")]
+        postal_code: String,
+        #[form(default)]
+        age: u8,
+        #[form(default)]
+        home: Address,
+        #[form(skip)]
+        #[allow(dead_code)]
+        id: std::cell::Cell<u64>,
+        #[form(flatten)]
+        location: Point,
+    }
+
+    #[test]
+    fn test_field_attributes() {
+        let mut contact = Contact::default();
+        let mut fields = vec![];
+
+        contact.fields("", &mut fields);
+        assert_eq!(fields, vec!["postalCode", "age", "home.city", "0", "1"]);
+
+        contact.set_value("postalCode", "K2P").unwrap();
+        contact.set_value("1", "3").unwrap();
+        assert_eq!(contact.value("postalCode"), "K2P");
+        assert_eq!(contact.location, Point(0.0, 3.0));
+
+        contact.set_value("age", "7").unwrap();
+        contact.set_value("age", "-1").unwrap();
+        assert_eq!(contact.age, 0);
+
+        // The default only replaces inputs the field can't convert, not unknown paths
+        contact.set_value("home.city", "Ghent").unwrap();
+        let error = contact.set_value("home.street", "x").unwrap_err();
+        assert_eq!(
+            (error.code.as_ref(), error.path.as_str()),
+            ("unknown_field", "home.street")
+        );
+        assert_eq!(contact.home.city, "Ghent");
+    }
+
+    mod tags {
+        use crate::ConversionError;
+
+        pub fn format(tags: &[String]) -> String {
+            tags.join(", ")
+        }
+
+        pub fn parse(value: &str) -> Result<Vec<String>, ConversionError> {
+            Ok(value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_owned)
+                .collect())
+        }
+    }
+
+    mod cents {
+        use crate::ConversionError;
+
+        pub fn format(cents: &i64) -> String {
+            format!("{}.{:02}", cents / 100, cents % 100)
+        }
+
+        pub fn parse(value: &str) -> Result<i64, ConversionError> {
+            let (units, cents) = value.split_once('.').unwrap_or((value, "0"));
+
+            match (
+                units.parse::<i64>(),
+                format!("{:0<2}", cents).parse::<i64>(),
+            ) {
+                (Ok(units), Ok(cents)) if cents < 100 => Ok(units * 100 + cents),
+                _ => Err(ConversionError::invalid::<i64>(value)),
+            }
+        }
+    }
+
+    #[derive(crate::FormValue, Default)]
+    struct Article {
+        #[form(with = "tags")]
+        tags: Vec<String>,
+        #[form(with = "cents")]
+        price: i64,
+    }
+
+    #[test]
+    fn test_with_attribute() {
+        let mut article = Article::default();
+        let mut fields = vec![];
+
+        article.fields("", &mut fields);
+        assert_eq!(fields, vec!["tags", "price"]);
+
+        article.set_value("tags", "rust, yew,,forms").unwrap();
+        article.set_value("price", "12.3").unwrap();
+        assert_eq!(article.tags, vec!["rust", "yew", "forms"]);
+        assert_eq!(article.price, 1230);
+        assert_eq!(article.value("tags"), "rust, yew, forms");
+        assert_eq!(article.value("price"), "12.30");
+
+        let error = article.set_value("price", "12.345").unwrap_err();
+        assert_eq!(error.path, "price");
+        let error = article.set_value("price.cents", "5").unwrap_err();
+        assert_eq!(
+            (error.code.as_ref(), error.path.as_str()),
+            ("unknown_field", "price.cents")
+        );
+        assert_eq!(Article::kind("price"), Some(ValueKind::Text));
+
+        // `cents` rejects an empty input, a new article starts from the default price
+        assert_eq!(Article::new_value().map(|article| article.price), Some(0));
+        let mut article: Option<Article> = None;
+        article.set_value("$present", "true").unwrap();
+        assert!(article.is_some());
+    }
+
+    #[derive(crate::Model, validator::Validate, PartialEq, Clone)]
+    struct Registration {
+        #[validate(length(min = 1, max = 40))]
+        #[form(label = "First name")]
+        first_name: String,
+        #[validate(range(min = 1, max = 99))]
+        #[form(help = "How many tickets")]
+        quantity: u32,
+        #[validate(length(min = 2))]
+        nickname: Option<String>,
+        newsletter: bool,
+    }
+
+    #[test]
+    fn test_field_meta() {
+        let first_name = Registration::field_meta("first_name").unwrap();
+        assert_eq!(first_name.kind, ValueKind::Text);
+        assert!(first_name.required);
+        assert_eq!(first_name.min_length, Some(1));
+        assert_eq!(first_name.max_length, Some(40));
+        assert_eq!(first_name.label.as_deref(), Some("First name"));
+
+        let quantity = Registration::field_meta("quantity").unwrap();
+        assert_eq!(quantity.kind, ValueKind::Integer);
+        assert!(quantity.required);
+        assert_eq!((quantity.min, quantity.max), (Some(1.0), Some(99.0)));
+        assert_eq!(quantity.help.as_deref(), Some("How many tickets"));
+
+        // `validator` accepts a `None` whatever its length rules
+        let nickname = Registration::field_meta("nickname").unwrap();
+        assert!(nickname.optional && !nickname.required);
+        assert_eq!(nickname.min_length, Some(2));
+        assert!(!Registration::field_meta("newsletter").unwrap().required);
+        assert_eq!(Registration::field_meta("unknown"), None);
+    }
+
+    #[test]
+    fn test_html_pattern() {
+        assert_eq!(
+            FieldMeta::html_pattern("^[A-Z]{2}$").as_deref(),
+            Some("[A-Z]{2}")
+        );
+        assert_eq!(FieldMeta::html_pattern("[a-z]+"), None);
+        assert_eq!(FieldMeta::html_pattern("^[a-z]+"), None);
+        assert_eq!(FieldMeta::html_pattern("^a|b$"), None);
+        assert_eq!(FieldMeta::html_pattern("^(?i)abc$"), None);
+        assert_eq!(FieldMeta::html_pattern(r"^\d+\$"), None);
+        assert_eq!(
+            FieldMeta::html_pattern(r"^\d+\\$").as_deref(),
+            Some(r"\d+\\")
+        );
+    }
+
+    #[derive(crate::FormValue, Clone, Default, Debug, PartialEq)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(crate::FormValue, Default)]
+    struct Shape {
+        origin: [f64; 3],
+        size: (u8, u8),
+        address: Box<Address>,
+        shared: std::rc::Rc<Address>,
+        name: std::borrow::Cow<'static, str>,
+    }
+
+    #[test]
+    fn test_array_tuple_and_pointer_values() {
+        let shared = std::rc::Rc::new(Address::default());
+        let mut shape = Shape {
+            shared: shared.clone(),
+            ..Default::default()
+        };
+        let mut fields = vec![];
+
+        shape.fields("", &mut fields);
+        assert_eq!(
+            fields,
+            vec![
+                "origin.0",
+                "origin.1",
+                "origin.2",
+                "size.0",
+                "size.1",
+                "address.city",
+                "shared.city",
+                "name"
+            ]
+        );
+
+        shape.set_value("origin.2", "1.5").unwrap();
+        shape.set_value("size.1", "4").unwrap();
+        shape.set_value("address.city", "Ghent").unwrap();
+        shape.set_value("shared.city", "Leuven").unwrap();
+        shape.set_value("name", "cube").unwrap();
+        assert_eq!(shape.origin, [0.0, 0.0, 1.5]);
+        assert_eq!(shape.size, (0, 4));
+        assert_eq!(shape.value("address.city"), "Ghent");
+        assert_eq!(shape.value("name"), "cube");
+        assert_eq!(shape.shared.city, "Leuven");
+        assert_eq!(shared.city, "");
+
+        assert_eq!(shape.set_value("size.0", "x").unwrap_err().path, "size.0");
+        assert_eq!(Shape::kind("origin.1"), Some(ValueKind::Number));
+        assert_eq!(Shape::kind("origin.3"), None);
+    }
+
+    /// A scalar implemented by hand, as a user of the crate would
+    #[derive(Debug, PartialEq)]
+    struct Percent(u8);
+
+    impl FormValue for Percent {
+        fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+            field_path.is_empty().then(|| self.0.to_string().into())
+        }
+
+        fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+            if !field_path.is_empty() {
+                return Err(ConversionError::unknown_field::<Self>(field_path, value));
+            }
+
+            *self = Self::parse_value(value)?;
+            Ok(())
+        }
+
+        fn parse_value(value: &str) -> Result<Self, ConversionError> {
+            match value.parse::<u8>() {
+                Ok(percent) if percent <= 100 => Ok(Percent(percent)),
+                Ok(_) => Err(ConversionError::new(
+                    "Percent",
+                    value.to_owned(),
+                    "out_of_range",
+                )),
+                Err(e) => Err(ConversionError::parse::<Self, _>(value, Some(&e))),
+            }
+        }
+    }
+
+    #[test]
+    fn test_optional_user_scalar() {
+        let mut percent: Option<Percent> = None;
+
+        percent.set_value("", "42").unwrap();
+        assert_eq!(percent, Some(Percent(42)));
+        assert_eq!(percent.value(""), "42");
+        assert_eq!(
+            percent.set_value("", "120").unwrap_err().code,
+            "out_of_range"
+        );
+        assert_eq!(percent, Some(Percent(42)));
+
+        percent.set_value("", "").unwrap();
+        assert_eq!(percent, None);
+
+        let mut address: Option<FormValueWrapper<std::net::Ipv4Addr>> = None;
+
+        address.set_value("", "10.0.0.1").unwrap();
+        assert_eq!(address.value(""), "10.0.0.1");
+        assert_eq!(address.set_value("", "10.0.0").unwrap_err().code, "invalid");
+    }
 }
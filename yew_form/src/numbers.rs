@@ -0,0 +1,342 @@
+//! Locale-aware formatting of number fields.
+//!
+//! A [`NumberFormat`] converts between the canonical representation of a number, as returned by
+//! [`FormValue::value`](crate::model::FormValue::value), and the representation shown in the form.
+//! It works on the digits rather than on a float, so values round-trip exactly.
+
+use yew::AttrValue;
+
+use crate::model::matches_field_pattern;
+
+#[cfg(feature = "rust_decimal")]
+use crate::error::ConversionError;
+#[cfg(feature = "rust_decimal")]
+use crate::model::{FormValue, ValueKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Separates groups of three digits in the integer part, if set
+    pub grouping_separator: Option<char>,
+    /// The number of decimals numbers are shown with, if fixed.
+    /// Only applies to fields that are not integers.
+    pub precision: Option<usize>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            decimal_separator: '.',
+            grouping_separator: None,
+            precision: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// The separators commonly used for `locale`, e.g. `de` or `fr-CA`.
+    /// Unknown locales use `.` as decimal separator and `,` for grouping.
+    pub fn locale(locale: &str) -> Self {
+        let language = locale.split('-').next().unwrap_or(locale);
+
+        let (decimal_separator, grouping_separator) = match (language, locale) {
+            (_, "de-CH" | "it-CH") => ('.', '\''),
+            ("de" | "nl" | "es" | "it" | "pt" | "da" | "id" | "tr" | "el" | "ro", _) => (',', '.'),
+            ("fr" | "ru" | "sv" | "nb" | "no" | "fi" | "cs" | "pl" | "sk" | "uk" | "hu", _) => {
+                (',', '\u{a0}')
+            }
+            _ => ('.', ','),
+        };
+
+        NumberFormat {
+            decimal_separator,
+            grouping_separator: Some(grouping_separator),
+            precision: None,
+        }
+    }
+
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    pub fn without_grouping(mut self) -> Self {
+        self.grouping_separator = None;
+        self
+    }
+
+    /// Formats a canonical number like `-1234.5`, values that are not numbers are returned as is
+    pub fn format(&self, value: &str) -> String {
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        if integer.is_empty()
+            || !(integer.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit())
+        {
+            return value.to_owned();
+        }
+
+        let (integer, fraction) = match self.precision {
+            Some(precision) => round(integer, fraction, precision),
+            None => (integer.to_owned(), fraction.to_owned()),
+        };
+
+        let mut formatted = String::new();
+
+        if negative {
+            formatted.push('-');
+        }
+
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                formatted.extend(self.grouping_separator);
+            }
+            formatted.push(digit);
+        }
+
+        if !fraction.is_empty() {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(&fraction);
+        }
+
+        formatted
+    }
+
+    /// Parses a number typed in this format to its canonical representation,
+    /// returns [`None`] if the input is not a number in this format.
+    pub fn parse(&self, input: &str) -> Option<String> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Some(String::new());
+        }
+
+        let (sign, digits) = match input.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", input.strip_prefix('+').unwrap_or(input)),
+        };
+        let (integer, fraction) = match digits.split_once(self.decimal_separator) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let integer = match self.grouping_separator {
+            Some(separator) if integer.contains(|c| is_separator(c, separator)) => {
+                let groups: Vec<&str> = integer.split(|c| is_separator(c, separator)).collect();
+
+                // Only accept separators between groups of three digits, so "5.99" isn't read as 599 in German
+                if groups[0].is_empty()
+                    || groups[0].len() > 3
+                    || groups[1..].iter().any(|g| g.len() != 3)
+                {
+                    return None;
+                }
+
+                groups.concat()
+            }
+            _ => integer.to_owned(),
+        };
+
+        let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+
+        if integer.is_empty() || !all_digits(&integer) || !fraction.into_iter().all(all_digits) {
+            return None;
+        }
+
+        Some(match fraction {
+            Some(fraction) if !fraction.is_empty() => format!("{}{}.{}", sign, integer, fraction),
+            _ => format!("{}{}", sign, integer),
+        })
+    }
+}
+
+/// Spaces are interchangeable when grouping with a (non-breaking) space
+fn is_separator(c: char, separator: char) -> bool {
+    c == separator || (separator.is_whitespace() && c.is_whitespace())
+}
+
+/// Rounds half up to `precision` decimals, padding with zeros
+fn round(integer: &str, fraction: &str, precision: usize) -> (String, String) {
+    let mut digits: Vec<u8> = integer
+        .bytes()
+        .chain(
+            fraction
+                .bytes()
+                .chain(std::iter::repeat(b'0'))
+                .take(precision),
+        )
+        .collect();
+
+    if matches!(fraction.as_bytes().get(precision), Some(b'5'..=b'9')) {
+        let mut i = digits.len();
+
+        loop {
+            if i == 0 {
+                digits.insert(0, b'1');
+                break;
+            }
+
+            i -= 1;
+
+            if digits[i] == b'9' {
+                digits[i] = b'0';
+            } else {
+                digits[i] += 1;
+                break;
+            }
+        }
+    }
+
+    let digits = String::from_utf8(digits).unwrap_or_default();
+    let (integer, fraction) = digits.split_at(digits.len() - precision);
+
+    (integer.to_owned(), fraction.to_owned())
+}
+
+/// The number formats of a form, for the whole form and for specific fields
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct NumberFormats {
+    form: Option<NumberFormat>,
+    /// Field path patterns, where `*` matches any single segment
+    fields: Vec<(AttrValue, NumberFormat)>,
+}
+
+impl NumberFormats {
+    pub(crate) fn set_form(&mut self, format: NumberFormat) {
+        self.form = Some(format);
+    }
+
+    pub(crate) fn set_field(&mut self, pattern: AttrValue, format: NumberFormat) {
+        self.fields.retain(|(p, _)| *p != pattern);
+        self.fields.push((pattern, format));
+    }
+
+    /// The format of `field_path`, the most recently set matching field pattern wins over the form's format
+    pub(crate) fn get(&self, field_path: &str) -> Option<&NumberFormat> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(pattern, _)| matches_field_pattern(pattern, field_path))
+            .map(|(_, format)| format)
+            .or(self.form.as_ref())
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl crate::error::ParseErrorCode for rust_decimal::Error {
+    fn code(&self) -> &'static str {
+        "invalid_number"
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl FormValue for rust_decimal::Decimal {
+    fn try_value(&self, field_path: &str) -> Option<AttrValue> {
+        field_path.is_empty().then(|| self.to_string().into())
+    }
+
+    fn set_value(&mut self, field_path: &str, value: &str) -> Result<(), ConversionError> {
+        if !field_path.is_empty() {
+            return Err(ConversionError::unknown_field::<Self>(field_path, value));
+        }
+
+        *self = Self::parse_value(value)?;
+        Ok(())
+    }
+
+    fn kind(field_path: &str) -> Option<ValueKind> {
+        field_path.is_empty().then_some(ValueKind::Number)
+    }
+
+    fn parse_value(value: &str) -> Result<Self, ConversionError> {
+        value
+            .parse::<rust_decimal::Decimal>()
+            .map_err(|e| ConversionError::parse::<Self, _>(value, Some(&e)))
+    }
+
+    fn new_value() -> Option<Self> {
+        Some(Default::default())
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+crate::paths::impl_scalar_paths!(rust_decimal::Decimal);
+
+#[cfg(test)]
+mod tests {
+    use super::NumberFormat;
+    use crate::fixtures::Product;
+    use crate::form_state::FormState;
+    use crate::model::matches_field_pattern;
+
+    #[test]
+    fn test_number_format() {
+        let de = NumberFormat::locale("de").with_precision(2);
+
+        assert_eq!(de.format("1234.5"), "1.234,50");
+        assert_eq!(de.format("-999.995"), "-1.000,00");
+        assert_eq!(de.format("0.1"), "0,10");
+        assert_eq!(de.parse("1.234,50").as_deref(), Some("1234.50"));
+        assert_eq!(de.parse("5,99").as_deref(), Some("5.99"));
+        assert_eq!(de.parse("5.99"), None);
+        assert_eq!(de.parse("abc"), None);
+
+        let fr = NumberFormat::locale("fr-CA");
+        assert_eq!(fr.format("1234567"), "1\u{a0}234\u{a0}567");
+        assert_eq!(fr.parse("1 234 567,5").as_deref(), Some("1234567.5"));
+
+        assert!(matches_field_pattern("lines.*.price", "lines.3.price"));
+        assert!(!matches_field_pattern("lines.*.price", "lines.3.price.0"));
+    }
+
+    #[test]
+    fn test_form_number_format() {
+        let mut state = FormState::new(Product {
+            code: "12345".into(),
+            price: 1234.5,
+            stock: 1000,
+            discount: None,
+        });
+
+        state.set_number_format(NumberFormat::locale("de").with_precision(2));
+        assert_eq!(state.field("code").value(), "12345");
+        assert_eq!(state.field("price").value(), "1.234,50");
+        assert_eq!(state.field("stock").value(), "1.000");
+        assert_eq!(state.field("discount").value(), "");
+        assert!(!state.dirty());
+
+        state.set_value("price", "5,99");
+        state.set_value("discount", "0,5");
+        assert_eq!(state.model().price, 5.99);
+        assert_eq!(state.model().discount, Some(0.5));
+
+        state.set_value("price", "5.99");
+        assert!(!state.field_valid("price"));
+        assert_eq!(state.model().price, 5.99);
+
+        state.set_field_number_format(
+            "stock".into(),
+            NumberFormat::locale("fr").without_grouping(),
+        );
+        assert_eq!(state.field("stock").value(), "1000");
+        assert_eq!(state.field("price").value(), "5.99");
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_decimal_value() {
+        use crate::model::FormValue;
+        use rust_decimal::Decimal;
+
+        let de = NumberFormat::locale("de").with_precision(2);
+        let mut value = Decimal::new(123450, 2);
+
+        assert_eq!(de.format(&value.value("")), "1.234,50");
+        assert!(value.set_value("", &de.parse("9.999,95").unwrap()).is_ok());
+        assert_eq!(value, Decimal::new(999995, 2));
+        assert_eq!(value.set_value("", "x").unwrap_err().code, "invalid_number");
+    }
+}
@@ -0,0 +1,534 @@
+//! Typed field paths, so a misspelled or renamed field is caught by the compiler.
+//!
+//! `#[derive(Model)]` generates a builder for the paths of a struct, e.g.
+//! `Registration::paths().address().city()` is the path `address.city` of a `String` in a `Registration`.
+//! The paths can be given to [`Form`](crate::Form) and to the components in place of a string,
+//! a path of another model or of a value of the wrong type doesn't compile.
+//!
+//! ```
+//! # use validator::Validate;
+//! # use yew::prelude::*;
+//! # use yew_form::{CheckBox, Form, Input, Model};
+//! #[derive(Model, Validate, PartialEq, Clone)]
+//! struct Login {
+//!     name: String,
+//!     remember: bool,
+//! }
+//!
+//! fn login_fields(form: &Form<Login>) -> Html {
+//!     form.set_value(Login::paths().name(), "admin");
+//!
+//!     html! {
+//!         <>
+//!             <Input<Login> {form} field_name={Login::paths().name()} />
+//!             <CheckBox<Login> {form} field_name={Login::paths().remember()} />
+//!         </>
+//!     }
+//! }
+//! ```
+//!
+//! A `CheckBox` doesn't take the path of a `String`:
+//! ```compile_fail
+//! # use validator::Validate;
+//! # use yew::prelude::*;
+//! # use yew_form::{CheckBox, Form, Model};
+//! # #[derive(Model, Validate, PartialEq, Clone)]
+//! # struct Login {
+//! #     name: String,
+//! # }
+//! fn login_fields(form: &Form<Login>) -> Html {
+//!     html! { <CheckBox<Login> {form} field_name={Login::paths().name()} /> }
+//! }
+//! ```
+//!
+//! Nor does a form take the paths of another model:
+//! ```compile_fail
+//! # use validator::Validate;
+//! # use yew_form::{Form, Model};
+//! # #[derive(Model, Validate, PartialEq, Clone)]
+//! # struct Login {
+//! #     name: String,
+//! # }
+//! # #[derive(Model, Validate, PartialEq, Clone)]
+//! # struct Signup {
+//! #     name: String,
+//! # }
+//! fn fill_in(form: &Form<Login>) {
+//!     form.set_value(Signup::paths().name(), "admin");
+//! }
+//! ```
+//!
+//! The rows of an array are edited in place, it has no rows to push, remove or swap:
+//! ```compile_fail
+//! # use validator::Validate;
+//! # use yew_form::{Form, Model};
+//! # #[derive(Model, Validate, PartialEq, Clone)]
+//! # struct Scores {
+//! #     rounds: [u32; 3],
+//! # }
+//! fn add_round(form: &Form<Scores>) {
+//!     form.push(Scores::paths().rounds(), 0u32);
+//! }
+//! ```
+//! ```compile_fail
+//! # use validator::Validate;
+//! # use yew_form::{Form, Model};
+//! # #[derive(Model, Validate, PartialEq, Clone)]
+//! # struct Scores {
+//! #     rounds: [u32; 3],
+//! # }
+//! fn drop_round(form: &Form<Scores>) {
+//!     form.remove(Scores::paths().rounds(), 0);
+//! }
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    rc::Rc,
+};
+use yew::html::{ImplicitClone, IntoPropValue};
+use yew::AttrValue;
+
+use crate::files::FormFiles;
+use crate::model::{join_field_path, FormValueWrapper, PRESENT_FIELD};
+
+/// Values whose field paths in the model `M` can be built, see the [module documentation](self)
+pub trait FieldPaths<M> {
+    /// The builder of the paths of the value
+    type Paths;
+
+    /// The builder of the paths of a value at `path`
+    fn paths(path: AttrValue) -> Self::Paths;
+}
+
+/// A path of a field of the model `M`, as taken by the methods of [`Form`](crate::Form).
+/// Strings are paths of any model, checked when they are used, the typed paths only of their model.
+pub trait FieldOf<M>: AsRef<str> {}
+
+/// A path of a field of `M` holding a `V`, e.g. a [`FieldPath<M, V>`] or a string
+pub trait ValueOf<M, V>: FieldOf<M> {}
+
+/// A path of a list of `V` in `M`, e.g. a [`ListPath<M, V>`] or a string
+pub trait ListOf<M, V>: FieldOf<M> {}
+
+/// A path of a list in `M` whose rows can be removed or swapped, e.g. a [`ListPath`] or a string
+pub trait RowsOf<M>: FieldOf<M> {}
+
+/// A path of a map with values of `V` in `M`, e.g. a [`MapPath<M, V>`] or a string
+pub trait MapOf<M, V>: FieldOf<M> {}
+
+macro_rules! impl_string_paths {
+    ($($t:ty),+) => {
+        $(
+            impl<M> FieldOf<M> for $t {}
+            impl<M, V> ValueOf<M, V> for $t {}
+            impl<M, V> ListOf<M, V> for $t {}
+            impl<M> RowsOf<M> for $t {}
+            impl<M, V> MapOf<M, V> for $t {}
+        )+
+    };
+}
+
+impl_string_paths!(str, String, AttrValue, Cow<'_, str>);
+
+impl<M, S: FieldOf<M> + ?Sized> FieldOf<M> for &S {}
+impl<M, V, S: ValueOf<M, V> + ?Sized> ValueOf<M, V> for &S {}
+impl<M, V, S: ListOf<M, V> + ?Sized> ListOf<M, V> for &S {}
+impl<M, S: RowsOf<M> + ?Sized> RowsOf<M> for &S {}
+impl<M, V, S: MapOf<M, V> + ?Sized> MapOf<M, V> for &S {}
+
+macro_rules! path_type {
+    ($(#[$attr:meta])* $path:ident $(= $default:ty)?) => {
+        $(#[$attr])*
+        pub struct $path<M, V $(= $default)?> {
+            path: AttrValue,
+            value: PhantomData<fn() -> (M, V)>,
+        }
+
+        impl<M, V> $path<M, V> {
+            pub fn new(path: impl Into<AttrValue>) -> Self {
+                $path {
+                    path: path.into(),
+                    value: PhantomData,
+                }
+            }
+
+            pub fn path(&self) -> &AttrValue {
+                &self.path
+            }
+        }
+
+        // Implemented by hand, the model and the value don't have to implement the traits
+        impl<M, V> Clone for $path<M, V> {
+            fn clone(&self) -> Self {
+                Self::new(self.path.clone())
+            }
+        }
+
+        impl<M, V> ImplicitClone for $path<M, V> {}
+
+        impl<M, V> PartialEq for $path<M, V> {
+            fn eq(&self, other: &Self) -> bool {
+                self.path == other.path
+            }
+        }
+
+        impl<M, V> Debug for $path<M, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($path)).field(&self.path).finish()
+            }
+        }
+
+        impl<M, V> Display for $path<M, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.path)
+            }
+        }
+
+        impl<M, V> AsRef<str> for $path<M, V> {
+            fn as_ref(&self) -> &str {
+                &self.path
+            }
+        }
+
+        impl<M, V> FieldOf<M> for $path<M, V> {}
+
+        impl<M, V> From<$path<M, V>> for AttrValue {
+            fn from(path: $path<M, V>) -> Self {
+                path.path
+            }
+        }
+
+        impl<M, V> IntoPropValue<AttrValue> for $path<M, V> {
+            fn into_prop_value(self) -> AttrValue {
+                self.path
+            }
+        }
+
+        impl<M, V> IntoPropValue<AttrValue> for &$path<M, V> {
+            fn into_prop_value(self) -> AttrValue {
+                self.path.clone()
+            }
+        }
+
+        impl<M, V> IntoPropValue<Option<AttrValue>> for $path<M, V> {
+            fn into_prop_value(self) -> Option<AttrValue> {
+                Some(self.path)
+            }
+        }
+
+        impl<M, V> IntoPropValue<Option<AttrValue>> for &$path<M, V> {
+            fn into_prop_value(self) -> Option<AttrValue> {
+                Some(self.path.clone())
+            }
+        }
+    };
+}
+
+path_type!(
+    /// The path of a field of `M` holding a `V`
+    FieldPath
+);
+
+path_type!(
+    /// The path of a list of `V` in `M`, e.g. a `Vec<V>`
+    ListPath
+);
+
+path_type!(
+    /// The path of an array `V` in `M`, e.g. a `[T; N]`, whose rows are fixed
+    ArrayPath
+);
+
+path_type!(
+    /// The path of a map with values of `V` in `M`, e.g. a `HashMap<String, V>`
+    MapPath
+);
+
+path_type!(
+    /// The path of a tuple in `M`, whose elements are selected with `_0()`, `_1()`, ...
+    TuplePath
+);
+
+path_type!(
+    /// The path of an `Option<V>` in `M`, see [`PRESENT_FIELD`]
+    OptionPath
+);
+
+path_type!(
+    /// The `field_name` of a component editing a field of the model `M`, given as a string or a typed path.
+    /// Components editing a `V` only take the typed paths of a `V`, with `V = ()` any field is taken.
+    FieldName = ()
+);
+
+impl<M, V> ValueOf<M, V> for FieldPath<M, V> {}
+impl<M, V> ValueOf<M, Option<V>> for OptionPath<M, V> {}
+impl<M, V> ValueOf<M, V> for FieldName<M, V> {}
+impl<M, V> ValueOf<M, V> for ArrayPath<M, V> {}
+impl<M, V> ListOf<M, V> for ListPath<M, V> {}
+impl<M, V> RowsOf<M> for ListPath<M, V> {}
+impl<M, V> MapOf<M, V> for MapPath<M, V> {}
+
+macro_rules! impl_string_field_name {
+    ($($t:ty),+) => {
+        $(
+            impl<M, V> IntoPropValue<FieldName<M, V>> for $t {
+                fn into_prop_value(self) -> FieldName<M, V> {
+                    FieldName::new(self.to_owned())
+                }
+            }
+        )+
+    };
+}
+
+impl_string_field_name!(&'static str, String, AttrValue, &AttrValue);
+
+impl<M, V> IntoPropValue<FieldName<M>> for FieldPath<M, V> {
+    fn into_prop_value(self) -> FieldName<M> {
+        FieldName::new(self.path)
+    }
+}
+
+impl<M, V> IntoPropValue<FieldName<M>> for &FieldPath<M, V> {
+    fn into_prop_value(self) -> FieldName<M> {
+        FieldName::new(self.path.clone())
+    }
+}
+
+impl<M, V> IntoPropValue<FieldName<M>> for OptionPath<M, V> {
+    fn into_prop_value(self) -> FieldName<M> {
+        FieldName::new(self.path)
+    }
+}
+
+impl<M, V> IntoPropValue<FieldName<M>> for &OptionPath<M, V> {
+    fn into_prop_value(self) -> FieldName<M> {
+        FieldName::new(self.path.clone())
+    }
+}
+
+/// Lets the components editing a single type of value take its typed paths
+macro_rules! impl_typed_field_name {
+    ($($t:ty),+) => {
+        $(
+            impl<M> IntoPropValue<FieldName<M, $t>> for FieldPath<M, $t> {
+                fn into_prop_value(self) -> FieldName<M, $t> {
+                    FieldName::new(self.path)
+                }
+            }
+
+            impl<M> IntoPropValue<FieldName<M, $t>> for &FieldPath<M, $t> {
+                fn into_prop_value(self) -> FieldName<M, $t> {
+                    FieldName::new(self.path.clone())
+                }
+            }
+        )+
+    };
+}
+
+impl_typed_field_name!(bool, FormFiles);
+
+impl<M, V: FieldPaths<M>> ListPath<M, V> {
+    /// The path of the row at `index`
+    pub fn at(&self, index: usize) -> V::Paths {
+        V::paths(join_field_path(&self.path, &index.to_string()).into())
+    }
+}
+
+impl<M, T: FieldPaths<M>, const N: usize> ArrayPath<M, [T; N]> {
+    /// The path of the row at `index`
+    pub fn at(&self, index: usize) -> T::Paths {
+        T::paths(join_field_path(&self.path, &index.to_string()).into())
+    }
+}
+
+impl<M, V: FieldPaths<M>> MapPath<M, V> {
+    /// The path of the entry under `key`
+    pub fn key(&self, key: &str) -> V::Paths {
+        V::paths(join_field_path(&self.path, key).into())
+    }
+}
+
+impl<M, V> OptionPath<M, V> {
+    /// The path toggling the presence of an optional struct
+    pub fn present(&self) -> FieldPath<M, bool> {
+        FieldPath::new(join_field_path(&self.path, PRESENT_FIELD))
+    }
+}
+
+impl<M, V: FieldPaths<M>> OptionPath<M, V> {
+    /// The paths of the value when it is present
+    pub fn value(&self) -> V::Paths {
+        V::paths(self.path.clone())
+    }
+}
+
+/// Implements [`FieldPaths`] for values that are bound as a single field
+macro_rules! impl_scalar_paths {
+    ($($t:ty),+) => {
+        $(
+            impl<M> crate::paths::FieldPaths<M> for $t {
+                type Paths = crate::paths::FieldPath<M, Self>;
+
+                fn paths(path: yew::AttrValue) -> Self::Paths {
+                    crate::paths::FieldPath::new(path)
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(any(feature = "chrono", feature = "time", feature = "rust_decimal"))]
+pub(crate) use impl_scalar_paths;
+
+impl_scalar_paths!(bool, String, AttrValue, Cow<'static, str>, FormFiles);
+impl_scalar_paths!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl<M, T> FieldPaths<M> for FormValueWrapper<T> {
+    type Paths = FieldPath<M, Self>;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        FieldPath::new(path)
+    }
+}
+
+impl<M, T> FieldPaths<M> for Option<T> {
+    type Paths = OptionPath<M, T>;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        OptionPath::new(path)
+    }
+}
+
+impl<M, T> FieldPaths<M> for Vec<T> {
+    type Paths = ListPath<M, T>;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        ListPath::new(path)
+    }
+}
+
+impl<M, T, const N: usize> FieldPaths<M> for [T; N] {
+    type Paths = ArrayPath<M, Self>;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        ArrayPath::new(path)
+    }
+}
+
+impl<M, K, T, S> FieldPaths<M> for HashMap<K, T, S> {
+    type Paths = MapPath<M, T>;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        MapPath::new(path)
+    }
+}
+
+impl<M, K, T> FieldPaths<M> for BTreeMap<K, T> {
+    type Paths = MapPath<M, T>;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        MapPath::new(path)
+    }
+}
+
+impl<M, T: FieldPaths<M>> FieldPaths<M> for Box<T> {
+    type Paths = T::Paths;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        T::paths(path)
+    }
+}
+
+impl<M, T: FieldPaths<M>> FieldPaths<M> for Rc<T> {
+    type Paths = T::Paths;
+
+    fn paths(path: AttrValue) -> Self::Paths {
+        T::paths(path)
+    }
+}
+
+macro_rules! impl_tuple_paths {
+    ($($method:ident $index:tt: $t:ident),+) => {
+        impl<M, $($t),+> FieldPaths<M> for ($($t,)+) {
+            type Paths = TuplePath<M, Self>;
+
+            fn paths(path: AttrValue) -> Self::Paths {
+                TuplePath::new(path)
+            }
+        }
+
+        impl<M, $($t: FieldPaths<M>),+> TuplePath<M, ($($t,)+)> {
+            $(
+                #[doc = concat!("The path of the element at index ", stringify!($index))]
+                pub fn $method(&self) -> $t::Paths {
+                    $t::paths(join_field_path(&self.path, stringify!($index)).into())
+                }
+            )+
+        }
+    };
+}
+
+impl_tuple_paths!(_0 0: A);
+impl_tuple_paths!(_0 0: A, _1 1: B);
+impl_tuple_paths!(_0 0: A, _1 1: B, _2 2: C);
+impl_tuple_paths!(_0 0: A, _1 1: B, _2 2: C, _3 3: D);
+impl_tuple_paths!(_0 0: A, _1 1: B, _2 2: C, _3 3: D, _4 4: E);
+impl_tuple_paths!(_0 0: A, _1 1: B, _2 2: C, _3 3: D, _4 4: E, _5 5: F);
+impl_tuple_paths!(_0 0: A, _1 1: B, _2 2: C, _3 3: D, _4 4: E, _5 5: F, _6 6: G);
+impl_tuple_paths!(_0 0: A, _1 1: B, _2 2: C, _3 3: D, _4 4: E, _5 5: F, _6 6: G, _7 7: H);
+
+#[cfg(test)]
+mod tests {
+    use super::FieldPath;
+    use crate::{FormValue, Model};
+    use std::collections::BTreeMap;
+    use validator::Validate;
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(FormValue, PartialEq, Clone, Debug)]
+    enum Contact {
+        Email { email: String },
+        Phone { number: String },
+    }
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    struct Registration {
+        #[form(rename = "login")]
+        name: String,
+        #[form(flatten)]
+        address: Address,
+        shipping: Option<Address>,
+        previous: Vec<Address>,
+        tags: BTreeMap<String, String>,
+        size: (u8, u8),
+        scores: [u8; 3],
+        contact: Contact,
+    }
+
+    #[test]
+    fn test_field_paths() {
+        let fields = Registration::paths();
+
+        assert_eq!(fields.name().as_ref(), "login");
+        assert_eq!(fields.address().city().as_ref(), "city");
+        assert_eq!(fields.shipping().present().as_ref(), "shipping.$present");
+        assert_eq!(fields.shipping().value().city().as_ref(), "shipping.city");
+        assert_eq!(fields.previous().at(1).city().as_ref(), "previous.1.city");
+        assert_eq!(fields.tags().key("a").as_ref(), "tags.a");
+        assert_eq!(fields.size()._1().as_ref(), "size.1");
+        assert_eq!(fields.scores().at(2).as_ref(), "scores.2");
+        assert_eq!(fields.contact().variant_path().as_ref(), "contact.$variant");
+        assert_eq!(fields.contact().number().as_ref(), "contact.number");
+
+        let path: FieldPath<Registration, String> = fields.previous().at(0).city();
+        assert_eq!(yew::AttrValue::from(path), "previous.0.city");
+    }
+}
@@ -0,0 +1,261 @@
+//! Keeps the inputs of a form as a draft, so they survive a reload, see [`use_form_persisted`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo_timers::callback::Timeout;
+use serde_json::{json, Value};
+use yew::prelude::*;
+
+use crate::form::{use_form_state, Form};
+use crate::form_state::FormState;
+use crate::Model;
+
+/// How long the form has to be left unchanged before its draft is saved, in milliseconds
+const SAVE_DELAY: u32 = 500;
+
+/// Where the drafts of forms are kept
+pub trait DraftStorage {
+    fn load(&self, key: &str) -> Option<String>;
+    fn save(&self, key: &str, draft: &str);
+    fn remove(&self, key: &str);
+}
+
+/// Keeps drafts in the `localStorage` of the browser, they are kept until they are removed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LocalStorage;
+
+/// Keeps drafts in the `sessionStorage` of the browser, they are dropped when the tab is closed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SessionStorage;
+
+/// Keeps drafts in memory, for tests. Clones share their drafts.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MemoryStorage(Rc<RefCell<HashMap<String, String>>>);
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
+
+// A browser without storage, or a full one, doesn't keep drafts
+macro_rules! impl_web_storage {
+    ($storage:ident, $get:ident) => {
+        impl DraftStorage for $storage {
+            fn load(&self, key: &str) -> Option<String> {
+                $get()?.get_item(key).ok()?
+            }
+
+            fn save(&self, key: &str, draft: &str) {
+                if let Some(storage) = $get() {
+                    let _ = storage.set_item(key, draft);
+                }
+            }
+
+            fn remove(&self, key: &str) {
+                if let Some(storage) = $get() {
+                    let _ = storage.remove_item(key);
+                }
+            }
+        }
+    };
+}
+
+impl_web_storage!(LocalStorage, local_storage);
+impl_web_storage!(SessionStorage, session_storage);
+
+impl DraftStorage for MemoryStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        self.0.borrow().get(key).cloned()
+    }
+
+    fn save(&self, key: &str, draft: &str) {
+        self.0.borrow_mut().insert(key.to_owned(), draft.to_owned());
+    }
+
+    fn remove(&self, key: &str) {
+        self.0.borrow_mut().remove(key);
+    }
+}
+
+/// Like [`use_form`](crate::use_form), but the inputs are saved under `key` in the `localStorage`
+/// while they are edited and restored when the form is created again.
+#[hook]
+pub fn use_form_persisted<T, K, F>(key: K, init_fn: F) -> Form<T>
+where
+    T: Model,
+    K: Into<AttrValue>,
+    F: FnOnce() -> T,
+{
+    use_form_persisted_with(LocalStorage, key, init_fn)
+}
+
+/// Like [`use_form_persisted`], keeping the draft in `storage`.
+///
+/// Only the inputs that differ from the initial model are saved, once the form is left unchanged for a moment.
+/// Restored inputs keep the initial values of the model, so they show as dirty.
+/// A draft saved for another shape of the model, e.g. before a field was added or a field of its rows changed type,
+/// is discarded.
+#[hook]
+pub fn use_form_persisted_with<T, S, K, F>(storage: S, key: K, init_fn: F) -> Form<T>
+where
+    T: Model,
+    S: DraftStorage + 'static,
+    K: Into<AttrValue>,
+    F: FnOnce() -> T,
+{
+    let key: AttrValue = key.into();
+    let storage = use_memo(|_| storage, ());
+    let form = {
+        let storage = storage.clone();
+
+        use_form_state(
+            move |key: &AttrValue| {
+                let mut state = FormState::new(init_fn());
+
+                match storage
+                    .load(key)
+                    .and_then(|draft| decode_draft::<T>(&draft))
+                {
+                    Some(values) => state.restore_draft(values),
+                    None => storage.remove(key),
+                }
+
+                state
+            },
+            key.clone(),
+        )
+    };
+
+    {
+        let generation = form.generation();
+        let form = form.clone();
+
+        // Every change cancels the pending save, so the draft is saved once the edits pause
+        use_effect_with_deps(
+            move |(key, _)| {
+                let key = key.clone();
+                let timeout = Timeout::new(SAVE_DELAY, move || save_draft(&*storage, &key, &form));
+
+                move || drop(timeout)
+            },
+            (key, generation),
+        );
+    }
+
+    form
+}
+
+fn save_draft<T: Model>(storage: &dyn DraftStorage, key: &str, form: &Form<T>) {
+    match form.draft_values() {
+        values if values.is_empty() => storage.remove(key),
+        values => storage.save(key, &encode_draft::<T>(values)),
+    }
+}
+
+/// Identifies the shape of the model from the kind of value at the paths of a new value and at the `saved` paths,
+/// so a draft is discarded when one of its paths no longer exists or holds another kind of value,
+/// e.g. after a field of the rows of a list changed type.
+fn model_version<T: Model>(saved: &[&str]) -> String {
+    let mut fields = vec![];
+
+    if let Some(model) = T::new_value() {
+        model.fields("", &mut fields);
+    }
+
+    let mut paths: Vec<&str> = fields
+        .iter()
+        .map(|path| path.as_str())
+        .chain(saved.iter().copied())
+        .collect();
+    paths.sort_unstable();
+    paths.dedup();
+
+    // FNV-1a, which unlike the hasher of the standard library doesn't change between releases
+    let hash = paths
+        .iter()
+        .map(|path| format!("{}:{:?}", path, T::kind(path)))
+        .flat_map(|s| s.into_bytes().into_iter().chain(Some(0)))
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+    format!("{:016x}", hash)
+}
+
+fn encode_draft<T: Model>(values: Vec<(AttrValue, AttrValue)>) -> String {
+    let paths: Vec<&str> = values.iter().map(|(path, _)| path.as_str()).collect();
+    let version = model_version::<T>(&paths);
+    let values: serde_json::Map<String, Value> = values
+        .into_iter()
+        .map(|(path, value)| (path.to_string(), Value::from(value.as_str())))
+        .collect();
+
+    json!({ "version": version, "values": values }).to_string()
+}
+
+fn decode_draft<T: Model>(draft: &str) -> Option<Vec<(AttrValue, AttrValue)>> {
+    let draft: Value = serde_json::from_str(draft).ok()?;
+    let values = draft["values"].as_object()?;
+    let paths: Vec<&str> = values.keys().map(|path| path.as_str()).collect();
+
+    if draft["version"].as_str()? != model_version::<T>(&paths) {
+        return None;
+    }
+
+    values
+        .iter()
+        .map(|(path, value)| Some((path.clone().into(), value.as_str()?.to_owned().into())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_draft, encode_draft, DraftStorage, MemoryStorage};
+    use crate::fixtures::{v1, v2, Account, AccountV2};
+    use crate::form_state::FormState;
+
+    #[test]
+    fn test_draft() {
+        let storage = MemoryStorage::default();
+        let account = Account {
+            name: "Ann".into(),
+            age: 30,
+        };
+        let mut state = FormState::new(account.clone());
+
+        state.set_value("name", "");
+        state.set_value("age", "x");
+        storage.save("account", &encode_draft::<Account>(state.draft_values()));
+
+        let draft = storage.load("account").unwrap();
+        assert_eq!(decode_draft::<AccountV2>(&draft), None);
+
+        let mut state = FormState::new(account);
+        state.restore_draft(decode_draft::<Account>(&draft).unwrap());
+        assert_eq!(state.model().name, "");
+        assert_eq!(state.field("name").initial_value(), "Ann");
+        assert!(!state.field_valid("name"));
+        assert_eq!(state.field("age").value(), "x");
+        assert!(!state.field_valid("age"));
+        assert!(state.dirty());
+    }
+
+    #[test]
+    fn test_draft_nested_shape() {
+        let mut state = FormState::new(v1::Order {
+            lines: vec![v1::Line { amount: 1 }],
+        });
+
+        state.set_value("lines.0.amount", "2");
+        let draft = encode_draft::<v1::Order>(state.draft_values());
+
+        // The rows of both versions are empty in a new value, only the saved row tells them apart
+        assert!(decode_draft::<v1::Order>(&draft).is_some());
+        assert_eq!(decode_draft::<v2::Order>(&draft), None);
+    }
+}
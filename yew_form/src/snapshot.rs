@@ -0,0 +1,168 @@
+use crate::form_field::FormField;
+
+/// The inputs, initial values and messages of every field of a form, see [`Form::snapshot`](crate::Form::snapshot).
+///
+/// With the `serde` feature it can be serialized, e.g. to keep a half-filled form and restore it later.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormSnapshot {
+    pub(crate) fields: Vec<FormField>,
+}
+
+impl FormSnapshot {
+    /// The fields in the order they are declared in the model
+    pub fn fields(&self) -> &[FormField] {
+        &self.fields
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use validator::ValidationError;
+
+    use crate::form_field::FormField;
+    use crate::form_state::FormState;
+    use crate::Model;
+
+    /// How a [`FormField`] is serialized. The conversion error isn't kept,
+    /// it is found again when the input is restored.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "FormField")]
+    struct SerdeField {
+        name: String,
+        value: String,
+        initial: String,
+        message: String,
+        valid: bool,
+        #[serde(default)]
+        disabled: bool,
+        #[serde(default)]
+        validation_error: Option<ValidationError>,
+        #[serde(default)]
+        external: bool,
+    }
+
+    impl Serialize for FormField {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            SerdeField {
+                name: self.name.to_string(),
+                value: self.value.to_string(),
+                initial: self.initial.to_string(),
+                message: self.message.to_string(),
+                valid: self.valid,
+                disabled: self.disabled,
+                validation_error: self.validation_error.clone(),
+                external: self.external,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for FormField {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let field = SerdeField::deserialize(deserializer)?;
+
+            Ok(FormField {
+                name: field.name.into(),
+                value: field.value.into(),
+                initial: field.initial.into(),
+                message: field.message.into(),
+                valid: field.valid,
+                disabled: field.disabled,
+                pending: false,
+                external: field.external,
+                conversion_error: None,
+                validation_error: field.validation_error,
+            })
+        }
+    }
+
+    /// Serialized as its [`FormSnapshot`](super::FormSnapshot), without the model
+    impl<T: Model> Serialize for FormState<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.snapshot().serialize(serializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{card_order, invoice, line, Payment};
+    use crate::form_state::FormState;
+
+    #[test]
+    fn test_snapshot_restore() {
+        let model = invoice(&[("a", 1), ("b", 2)]);
+        let mut state = FormState::new(model.clone());
+
+        state.set_value("lines.0.name", "");
+        state.set_value("lines.1.amount", "x");
+        let snapshot = state.snapshot();
+
+        #[cfg(feature = "serde")]
+        let snapshot: super::FormSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        // A row was removed since the snapshot was taken
+        let mut state = FormState::new(invoice(&[("a", 1)]));
+        state.restore(snapshot.clone());
+        assert_eq!(state.model().lines, vec![line("", 1)]);
+        assert!(state.dirty());
+        assert!(!state.field_valid("lines.0.name"));
+        assert_eq!(
+            state.field_message("lines.0.name"),
+            snapshot.fields()[0].message()
+        );
+
+        let mut state = FormState::new(model);
+        state.restore(snapshot);
+        assert_eq!(state.field("lines.1.amount").value(), "x");
+        assert!(state.field("lines.1.amount").conversion_error().is_some());
+        assert_eq!(state.model().lines[1].amount, 2);
+    }
+
+    #[test]
+    fn test_restore_external_errors() {
+        let model = invoice(&[("a", 1), ("b", 2)]);
+        let mut state = FormState::new(model.clone());
+
+        state.set_errors([("lines.1.name", vec!["Sold out"])].into_iter().collect());
+        let snapshot = state.snapshot();
+
+        #[cfg(feature = "serde")]
+        let snapshot: super::FormSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        let mut state = FormState::new(model);
+        state.restore(snapshot);
+        assert!(state.field("lines.1.name").external());
+
+        // The restored error is kept when the form is validated, until the value changes
+        state.validate();
+        assert_eq!(state.field_message("lines.1.name"), "Sold out");
+        state.set_value("lines.1.name", "c");
+        assert!(state.field_valid("lines.1.name"));
+    }
+
+    #[test]
+    fn test_restore_variant() {
+        let card = card_order();
+        let mut state = FormState::new(card.clone());
+
+        state.set_value("payment.$variant", "Iban");
+        state.set_value("payment.iban", "BE68");
+        let snapshot = state.snapshot();
+
+        let mut state = FormState::new(card);
+        state.restore(snapshot);
+        assert_eq!(
+            state.model().payment,
+            Payment::Iban {
+                iban: "BE68".into()
+            }
+        );
+        assert_eq!(state.field("payment.$variant").initial_value(), "Card");
+        assert!(state.dirty());
+    }
+}
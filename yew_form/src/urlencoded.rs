@@ -0,0 +1,182 @@
+//! `application/x-www-form-urlencoded` data, as posted by a classic HTML form.
+//!
+//! The keys are the paths of the fields, e.g. `address.city=Paris&lines.0.price=12.5`.
+//! When decoding, a key repeated for a list adds a row for every value, e.g. `tags=a&tags=b`,
+//! and rows and map entries are created for the paths that need them, e.g. `lines.2.price`.
+//! [`Form::load_urlencoded`](crate::Form::load_urlencoded) decodes into a form, whose fields show the conversion errors.
+
+use yew::AttrValue;
+
+use crate::error::FormError;
+use crate::form_state::FormState;
+use crate::model::{join_field_path, FormValue};
+use crate::Model;
+
+pub(crate) fn encode<T: FormValue + ?Sized>(value: &T) -> String {
+    let mut paths = vec![];
+    value.fields("", &mut paths);
+
+    paths
+        .iter()
+        .fold(
+            form_urlencoded::Serializer::new(String::new()),
+            |mut serializer, path| {
+                serializer.append_pair(path, &value.value(path));
+                serializer
+            },
+        )
+        .finish()
+}
+
+/// Sets the values through a [`FormState`], so they are converted as if they were entered in the form
+pub(crate) fn decode<T: Model>(model: T, query: &str) -> Result<T, Vec<FormError>> {
+    let mut state = FormState::new(model);
+
+    match load(&mut state, query) {
+        errors if errors.is_empty() => Ok(state.model),
+        errors => Err(errors),
+    }
+}
+
+/// Sets the values as if they were entered, so the fields keep the inputs that can't be converted.
+/// Returns the errors of the rejected inputs and of the paths that don't exist.
+pub(crate) fn load<T: Model>(state: &mut FormState<T>, query: &str) -> Vec<FormError> {
+    let values: Vec<(AttrValue, AttrValue)> = form_urlencoded::parse(query.as_bytes())
+        .map(|(key, value)| {
+            let path = match state.model.list_mut(&key).map(|list| list.len()) {
+                Some(index) => {
+                    state.push_new(&key);
+                    join_field_path(&key, &index.to_string())
+                }
+                None => {
+                    add_entries(state, &key);
+                    key.into_owned()
+                }
+            };
+
+            (path.into(), value.into_owned().into())
+        })
+        .collect();
+
+    state.try_set_values(values)
+}
+
+/// Adds the rows and map entries missing on the way to `field_path`
+fn add_entries<T: Model>(state: &mut FormState<T>, field_path: &str) {
+    let segments: Vec<&str> = field_path.split('.').collect();
+
+    for i in 1..segments.len() {
+        let prefix = segments[..i].join(".");
+        let segment = segments[i];
+
+        if let Some(len) = state.model.list_mut(&prefix).map(|list| list.len()) {
+            if let Ok(index) = segment.parse::<usize>() {
+                for _ in len..=index {
+                    if !state.push_new(&prefix) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            state.insert_new_key(&prefix, segment);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load;
+    use crate::form_state::FormState;
+    use crate::{FormError, FormValueWrapper, Model};
+    use std::any::type_name;
+    use std::collections::BTreeMap;
+    use std::net::Ipv4Addr;
+    use validator::Validate;
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    struct Line {
+        name: String,
+        quantity: u32,
+    }
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    struct Order {
+        customer: String,
+        tags: Vec<String>,
+        lines: Vec<Line>,
+        notes: BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn test_urlencoded() {
+        let order = Order {
+            customer: "Ann & Bob".into(),
+            tags: vec!["a".into(), "b".into()],
+            lines: vec![Line {
+                name: "Tea".into(),
+                quantity: 2,
+            }],
+            notes: BTreeMap::from([("gift".into(), "yes".into())]),
+        };
+
+        let query = order.to_urlencoded();
+        assert_eq!(
+            query,
+            "customer=Ann+%26+Bob&tags.0=a&tags.1=b&lines.0.name=Tea&lines.0.quantity=2&notes.gift=yes"
+        );
+        assert_eq!(Order::from_urlencoded(&query), Ok(order));
+
+        let order = Order::from_urlencoded("tags=a&tags=b&lines.1.quantity=3").unwrap();
+        assert_eq!(order.tags, ["a", "b"]);
+        assert_eq!(order.lines.len(), 2);
+        assert_eq!(order.lines[1].quantity, 3);
+
+        let errors = Order::from_urlencoded("lines.0.quantity=many&discount=5").unwrap_err();
+        assert!(matches!(&errors[0], FormError::Conversion(e) if e.path == "lines.0.quantity"));
+        assert_eq!(errors[1], FormError::UnknownField("discount".into()));
+    }
+
+    #[derive(Model, Validate, PartialEq, Clone, Debug)]
+    struct Server {
+        name: String,
+        address: FormValueWrapper<Ipv4Addr>,
+    }
+
+    #[test]
+    fn test_urlencoded_base_value() {
+        assert_eq!(
+            Server::from_urlencoded("name=db"),
+            Err(vec![FormError::NoNewValue(type_name::<Server>())])
+        );
+
+        let server = Server {
+            name: "web".into(),
+            address: FormValueWrapper(Ipv4Addr::LOCALHOST),
+        };
+        let server = server.with_urlencoded("name=db").unwrap();
+        assert_eq!(server.name, "db");
+        assert_eq!(*server.address, Ipv4Addr::LOCALHOST);
+    }
+
+    #[test]
+    fn test_load_urlencoded() {
+        let mut state = FormState::new(Order {
+            customer: "".into(),
+            tags: vec![],
+            lines: vec![],
+            notes: BTreeMap::new(),
+        });
+
+        let errors = load(
+            &mut state,
+            "customer=Ann&lines.1.quantity=many&notes.gift=yes",
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(state.model().customer, "Ann");
+        assert_eq!(state.model().lines.len(), 2);
+        assert_eq!(state.model().notes["gift"], "yes");
+        assert_eq!(state.field("lines.1.quantity").value(), "many");
+        assert!(state.field("lines.1.quantity").conversion_error().is_some());
+        assert!(state.dirty());
+    }
+}
@@ -0,0 +1,192 @@
+use quote::ToTokens;
+use syn::{Attribute, Lit, Meta, NestedMeta};
+
+/// The options set with `#[form(...)]` on a type, field or variant
+#[derive(Default)]
+pub struct FormAttributes {
+    pub rename: Option<String>,
+    pub transparent: bool,
+    /// The field is not bound to the form
+    pub skip: bool,
+    /// The paths of the field are announced as if they were fields of the parent
+    pub flatten: bool,
+    /// The field is reset to its default value when the input can't be converted
+    pub default: bool,
+    /// The module whose `format` and `parse` functions convert the field
+    pub with: Option<syn::Path>,
+    pub label: Option<String>,
+    pub help: Option<String>,
+}
+
+impl FormAttributes {
+    pub fn parse(attrs: &[Attribute]) -> FormAttributes {
+        let mut attributes = FormAttributes::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("form")) {
+            let list = match attr.parse_meta() {
+                Ok(Meta::List(list)) => list,
+                _ => panic!("Expected #[form(...)]"),
+            };
+
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("rename") => {
+                        match meta.lit {
+                            Lit::Str(lit) => attributes.rename = Some(lit.value()),
+                            _ => panic!("#[form(rename = \"...\")] expects a string"),
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("label") => {
+                        match meta.lit {
+                            Lit::Str(lit) => attributes.label = Some(lit.value()),
+                            _ => panic!("#[form(label = \"...\")] expects a string"),
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("help") => {
+                        match meta.lit {
+                            Lit::Str(lit) => attributes.help = Some(lit.value()),
+                            _ => panic!("#[form(help = \"...\")] expects a string"),
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("with") => {
+                        match meta.lit {
+                            Lit::Str(lit) => match lit.parse() {
+                                Ok(path) => attributes.with = Some(path),
+                                Err(_) => panic!("#[form(with = \"...\")] expects a module path"),
+                            },
+                            _ => panic!("#[form(with = \"...\")] expects a string"),
+                        }
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transparent") => {
+                        attributes.transparent = true
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                        attributes.skip = true
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("flatten") => {
+                        attributes.flatten = true
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                        attributes.default = true
+                    }
+                    nested => panic!(
+                        "Unknown form attribute `{}`",
+                        nested.to_token_stream().to_string().replace(' ', "")
+                    ),
+                }
+            }
+        }
+
+        if attributes.flatten && attributes.with.is_some() {
+            panic!("#[form(flatten)] can't be combined with #[form(with = \"...\")]");
+        }
+
+        if attributes.flatten && attributes.rename.is_some() {
+            panic!("#[form(flatten)] can't be combined with #[form(rename = \"...\")]");
+        }
+
+        attributes
+    }
+}
+
+/// The constraints of a field that can be read from its `#[validate(...)]` attributes.
+/// Other validations and values that aren't literals are ignored, they are left to the validator.
+#[derive(Default)]
+pub struct ValidateAttributes {
+    pub required: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_length: Option<u64>,
+    pub max_length: Option<u64>,
+    /// The path of the static `Regex`
+    pub regex: Option<syn::Path>,
+}
+
+impl ValidateAttributes {
+    pub fn parse(attrs: &[Attribute]) -> ValidateAttributes {
+        let mut attributes = ValidateAttributes::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("validate")) {
+            let list = match attr.parse_meta() {
+                Ok(Meta::List(list)) => list,
+                _ => continue,
+            };
+
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("required") => {
+                        attributes.required = true
+                    }
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("length") => {
+                        for (name, lit) in name_values(&list.nested) {
+                            match (name.as_str(), lit_u64(lit)) {
+                                ("min", Some(min)) => attributes.min_length = Some(min),
+                                ("max", Some(max)) => attributes.max_length = Some(max),
+                                ("equal", Some(equal)) => {
+                                    attributes.min_length = Some(equal);
+                                    attributes.max_length = Some(equal);
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("range") => {
+                        for (name, lit) in name_values(&list.nested) {
+                            match (name.as_str(), lit_f64(lit)) {
+                                ("min", Some(min)) => attributes.min = Some(min),
+                                ("max", Some(max)) => attributes.max = Some(max),
+                                _ => {}
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("regex") => {
+                        attributes.regex = lit_path(&meta.lit);
+                    }
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("regex") => {
+                        for (name, lit) in name_values(&list.nested) {
+                            if name == "path" {
+                                attributes.regex = lit_path(lit);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        attributes
+    }
+}
+
+fn name_values(
+    nested: &syn::punctuated::Punctuated<NestedMeta, syn::token::Comma>,
+) -> impl Iterator<Item = (String, &Lit)> {
+    nested.iter().filter_map(|nested| match nested {
+        NestedMeta::Meta(Meta::NameValue(meta)) => {
+            Some((meta.path.get_ident()?.to_string(), &meta.lit))
+        }
+        _ => None,
+    })
+}
+
+fn lit_u64(lit: &Lit) -> Option<u64> {
+    match lit {
+        Lit::Int(lit) => lit.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+fn lit_f64(lit: &Lit) -> Option<f64> {
+    match lit {
+        Lit::Int(lit) => lit.base10_parse().ok(),
+        Lit::Float(lit) => lit.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+fn lit_path(lit: &Lit) -> Option<syn::Path> {
+    match lit {
+        Lit::Str(lit) => lit.parse().ok(),
+        _ => None,
+    }
+}
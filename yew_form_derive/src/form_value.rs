@@ -0,0 +1,1024 @@
+use crate::attributes::{FormAttributes, ValidateAttributes};
+use quote::ToTokens;
+
+/// Implements `FormValue` for a struct, announcing every field under its name,
+/// or its position for tuple structs.
+///
+/// With `#[form(transparent)]` a single field struct is bound as the field itself.
+/// A field with `#[form(with = "module")]` is bound as a scalar converted by
+/// `module::format(&T) -> impl Into<AttrValue>` and `module::parse(&str) -> Result<T, ConversionError>`.
+pub fn derive_struct(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let fields = match ast.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => fields,
+        _ => panic!("Expected a struct"),
+    };
+
+    // Statements announcing the paths of every field, in declaration order
+    let mut announce: Vec<proc_macro2::TokenStream> = vec![];
+    let mut field_names: Vec<String> = vec![];
+    let mut field_getters: Vec<proc_macro2::TokenStream> = vec![];
+    let mut field_setters: Vec<proc_macro2::TokenStream> = vec![];
+    let mut field_kinds: Vec<proc_macro2::TokenStream> = vec![];
+    let mut field_metas: Vec<proc_macro2::TokenStream> = vec![];
+    // The fields bound through their own `FormValue` implementation, i.e. without `#[form(with = "...")]`
+    let mut field_members: Vec<syn::Member> = vec![];
+    let mut member_names: Vec<String> = vec![];
+    let mut field_types: Vec<&syn::Type> = vec![];
+    let mut flatten_members: Vec<syn::Member> = vec![];
+    let mut flatten_types: Vec<&syn::Type> = vec![];
+    // The initial value of every field when a new value is created for a sub-form
+    let mut new_fields: Vec<proc_macro2::TokenStream> = vec![];
+    let mut skip_members: Vec<syn::Member> = vec![];
+    // The methods of the builder of the field paths
+    let mut path_methods: Vec<proc_macro2::TokenStream> = vec![];
+
+    for (index, field) in fields.iter().enumerate() {
+        let attributes = FormAttributes::parse(&field.attrs);
+        let field_member = match field.ident {
+            Some(ref ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(index.into()),
+        };
+        let field_name = match field_member {
+            syn::Member::Named(ref ident) => ident.to_string(),
+            syn::Member::Unnamed(ref index) => index.index.to_string(),
+        };
+
+        let field_type = &field.ty;
+
+        if attributes.skip {
+            new_fields.push(quote! { #field_member: ::std::default::Default::default() });
+            skip_members.push(field_member);
+            continue;
+        }
+
+        new_fields.push(match attributes.with {
+            // The adapter may reject an empty input, e.g. an amount of cents
+            Some(_) => quote! { #field_member: ::std::default::Default::default() },
+            None => quote! { #field_member: <#field_type>::new_value()? },
+        });
+
+        // Types are bound through their `FormValue` implementation, references can't be changed
+        let supported = matches!(
+            field.ty,
+            syn::Type::Path(_)
+                | syn::Type::Array(_)
+                | syn::Type::Tuple(_)
+                | syn::Type::Paren(_)
+                | syn::Type::Group(_)
+        );
+
+        if attributes.with.is_none() && !supported {
+            panic!(
+                "Type `{}` of field `{}` is not supported",
+                field.ty.to_token_stream().to_string().replace(' ', ""),
+                field_name
+            );
+        }
+
+        let method = match field_member {
+            syn::Member::Named(ref ident) => ident.clone(),
+            syn::Member::Unnamed(ref index) => format_ident!("_{}", index.index),
+        };
+
+        if attributes.flatten {
+            path_methods.push(quote! {
+                pub fn #method(&self) -> <#field_type as ::yew_form::paths::FieldPaths<__M>>::Paths {
+                    <#field_type as ::yew_form::paths::FieldPaths<__M>>::paths(self.path.clone())
+                }
+            });
+            announce.push(quote! {
+                self.#field_member.fields(prefix, fields);
+            });
+            flatten_members.push(field_member);
+            flatten_types.push(&field.ty);
+            continue;
+        }
+
+        let field_name = attributes.rename.clone().unwrap_or(field_name);
+
+        if let Some(ref with) = attributes.with {
+            path_methods.push(quote! {
+                pub fn #method(&self) -> ::yew_form::paths::FieldPath<__M, #field_type> {
+                    ::yew_form::paths::FieldPath::new(::yew_form::join_field_path(&self.path, #field_name))
+                }
+            });
+            // The field is a scalar converted by the `format` and `parse` functions of the module
+            announce.push(quote! {
+                fields.push(::yew_form::join_field_path(prefix, #field_name).into());
+            });
+            field_getters.push(quote! {
+                suffix
+                    .is_empty()
+                    .then(|| ::yew::virtual_dom::AttrValue::from(#with::format(&self.#field_member)))
+            });
+            let set = if attributes.default {
+                quote! {
+                    self.#field_member = #with::parse(value).unwrap_or_default();
+                    ::std::result::Result::Ok(())
+                }
+            } else {
+                quote! {
+                    self.#field_member = #with::parse(value).map_err(|e| e.prefixed(#field_name))?;
+                    ::std::result::Result::Ok(())
+                }
+            };
+            // The field is bound as a whole, it has no nested paths
+            field_setters.push(quote! {
+                {
+                    if !suffix.is_empty() {
+                        return ::std::result::Result::Err(
+                            ::yew_form::ConversionError::unknown_field::<#field_type>(suffix, value)
+                                .prefixed(#field_name),
+                        );
+                    }
+
+                    #set
+                }
+            });
+            field_kinds.push(quote! {
+                suffix.is_empty().then_some(::yew_form::model::ValueKind::Text)
+            });
+            field_metas.push(meta(
+                field,
+                &attributes,
+                quote! {
+                    suffix
+                        .is_empty()
+                        .then(|| ::yew_form::FieldMeta::new(::yew_form::model::ValueKind::Text))
+                },
+            ));
+            field_names.push(field_name);
+            continue;
+        }
+
+        path_methods.push(path_method(&method, &field_name, field_type));
+        announce.push(quote! {
+            self.#field_member.fields(&::yew_form::join_field_path(prefix, #field_name), fields);
+        });
+        field_getters.push(quote! {
+            self.#field_member.try_value(suffix)
+        });
+        field_setters.push(if attributes.default {
+            // Only an input the field itself can't convert falls back to the default,
+            // unknown paths and the errors of nested fields are reported
+            quote! {
+                match self.#field_member.set_value(suffix, value) {
+                    ::std::result::Result::Err(e) if suffix.is_empty() && e.code != "unknown_field" => {
+                        self.#field_member = ::std::default::Default::default();
+                        ::std::result::Result::Ok(())
+                    }
+                    result => result.map_err(|e| e.prefixed(#field_name)),
+                }
+            }
+        } else {
+            quote! {
+                self.#field_member
+                    .set_value(suffix, value)
+                    .map_err(|e| e.prefixed(#field_name))
+            }
+        });
+        field_kinds.push(quote! {
+            <#field_type>::kind(suffix)
+        });
+        field_metas.push(meta(
+            field,
+            &attributes,
+            quote! { <#field_type>::field_meta(suffix) },
+        ));
+        field_members.push(field_member);
+        member_names.push(field_name.clone());
+        field_names.push(field_name);
+        field_types.push(&field.ty);
+    }
+
+    let struct_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    if FormAttributes::parse(&ast.attrs).transparent {
+        if field_members.len() != field_names.len() {
+            panic!("#[form(transparent)] can't be combined with #[form(with = \"...\")]");
+        }
+
+        let generics = paths_generics(ast);
+        let (paths_impl_generics, _, _) = generics.split_for_impl();
+        let field_member = match (field_members.as_slice(), flatten_members.as_slice()) {
+            ([field_member], []) => field_member,
+            _ => panic!("#[form(transparent)] struct must have exactly one field"),
+        };
+
+        return quote! {
+            impl #impl_generics ::yew_form::model::FormValue for #struct_name #ty_generics #where_clause {
+                fn fields(&self, prefix: &str, fields: &mut ::std::vec::Vec<::yew::virtual_dom::AttrValue>) {
+                    self.#field_member.fields(prefix, fields);
+                }
+
+                fn try_value(&self, field_path: &str) -> ::std::option::Option<::yew::virtual_dom::AttrValue> {
+                    self.#field_member.try_value(field_path)
+                }
+
+                fn set_value(&mut self, field_path: &str, value: &str) -> ::std::result::Result<(), ::yew_form::ConversionError> {
+                    self.#field_member.set_value(field_path, value)
+                }
+
+                fn has_field(&self, field_name: &str) -> bool {
+                    self.#field_member.has_field(field_name)
+                }
+
+                fn list_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut dyn ::yew_form::model::FormList> {
+                    self.#field_member.list_mut(field_path)
+                }
+
+                fn map_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut dyn ::yew_form::model::FormMap> {
+                    self.#field_member.map_mut(field_path)
+                }
+
+                fn files(&self, field_path: &str) -> ::std::option::Option<&::yew_form::files::FormFiles> {
+                    ::yew_form::model::FormValue::files(&self.#field_member, field_path)
+                }
+
+                fn files_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut ::yew_form::files::FormFiles> {
+                    self.#field_member.files_mut(field_path)
+                }
+
+                fn kind(field_path: &str) -> ::std::option::Option<::yew_form::model::ValueKind> {
+                    <#(#field_types)*>::kind(field_path)
+                }
+
+                fn field_meta(field_path: &str) -> ::std::option::Option<::yew_form::FieldMeta> {
+                    <#(#field_types)*>::field_meta(field_path)
+                }
+
+                fn parse_value(value: &str) -> ::std::result::Result<Self, ::yew_form::ConversionError> {
+                    ::std::result::Result::Ok(Self {
+                        #field_member: <#(#field_types)*>::parse_value(value)?,
+                        #(#skip_members: ::std::default::Default::default(),)*
+                    })
+                }
+
+                fn new_value() -> ::std::option::Option<Self> {
+                    ::std::option::Option::Some(Self { #(#new_fields,)* })
+                }
+            }
+
+            impl #paths_impl_generics ::yew_form::paths::FieldPaths<__M> for #struct_name #ty_generics #where_clause {
+                type Paths = <#(#field_types)* as ::yew_form::paths::FieldPaths<__M>>::Paths;
+
+                fn paths(path: ::yew::virtual_dom::AttrValue) -> Self::Paths {
+                    <#(#field_types)* as ::yew_form::paths::FieldPaths<__M>>::paths(path)
+                }
+            }
+        };
+    }
+
+    let paths = derive_paths(ast, &path_methods);
+
+    quote! {
+        impl #impl_generics ::yew_form::model::FormValue for #struct_name #ty_generics #where_clause {
+            fn fields(&self, prefix: &str, fields: &mut ::std::vec::Vec<::yew::virtual_dom::AttrValue>) {
+                #(#announce)*
+            }
+
+            fn try_value(&self, field_path: &str) -> ::std::option::Option<::yew::virtual_dom::AttrValue> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #field_names => #field_getters,
+                    )*
+                    _ => {
+                        #(
+                        if self.#flatten_members.has_field(field_name) {
+                            return self.#flatten_members.try_value(field_path);
+                        }
+                        )*
+
+                        ::std::option::Option::None
+                    }
+                }
+            }
+
+            fn set_value(&mut self, field_path: &str, value: &str) -> ::std::result::Result<(), ::yew_form::ConversionError> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #field_names => #field_setters,
+                    )*
+                    _ => {
+                        #(
+                        if self.#flatten_members.has_field(field_name) {
+                            return self.#flatten_members.set_value(field_path, value);
+                        }
+                        )*
+
+                        ::std::result::Result::Err(::yew_form::ConversionError::unknown_field::<Self>(field_path, value))
+                    }
+                }
+            }
+
+            fn has_field(&self, field_name: &str) -> bool {
+                [#(#field_names),*].contains(&field_name)
+                    #(|| self.#flatten_members.has_field(field_name))*
+            }
+
+            fn list_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut dyn ::yew_form::model::FormList> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #member_names => self.#field_members.list_mut(suffix),
+                    )*
+                    _ => {
+                        #(
+                        if self.#flatten_members.has_field(field_name) {
+                            return self.#flatten_members.list_mut(field_path);
+                        }
+                        )*
+
+                        ::std::option::Option::None
+                    }
+                }
+            }
+
+            fn map_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut dyn ::yew_form::model::FormMap> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #member_names => self.#field_members.map_mut(suffix),
+                    )*
+                    _ => {
+                        #(
+                        if self.#flatten_members.has_field(field_name) {
+                            return self.#flatten_members.map_mut(field_path);
+                        }
+                        )*
+
+                        ::std::option::Option::None
+                    }
+                }
+            }
+
+            fn files(&self, field_path: &str) -> ::std::option::Option<&::yew_form::files::FormFiles> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #member_names => ::yew_form::model::FormValue::files(&self.#field_members, suffix),
+                    )*
+                    _ => {
+                        #(
+                        if self.#flatten_members.has_field(field_name) {
+                            return ::yew_form::model::FormValue::files(&self.#flatten_members, field_path);
+                        }
+                        )*
+
+                        ::std::option::Option::None
+                    }
+                }
+            }
+
+            fn files_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut ::yew_form::files::FormFiles> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #member_names => self.#field_members.files_mut(suffix),
+                    )*
+                    _ => {
+                        #(
+                        if self.#flatten_members.has_field(field_name) {
+                            return self.#flatten_members.files_mut(field_path);
+                        }
+                        )*
+
+                        ::std::option::Option::None
+                    }
+                }
+            }
+
+            fn kind(field_path: &str) -> ::std::option::Option<::yew_form::model::ValueKind> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #field_names => #field_kinds,
+                    )*
+                    _ => ::std::option::Option::None #(.or_else(|| <#flatten_types>::kind(field_path)))*,
+                }
+            }
+
+            fn field_meta(field_path: &str) -> ::std::option::Option<::yew_form::FieldMeta> {
+                let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+                match field_name {
+                    #(
+                    #field_names => #field_metas,
+                    )*
+                    _ => ::std::option::Option::None #(.or_else(|| <#flatten_types>::field_meta(field_path)))*,
+                }
+            }
+
+            fn parse_value(value: &str) -> ::std::result::Result<Self, ::yew_form::ConversionError> {
+                ::std::result::Result::Err(::yew_form::ConversionError::invalid::<Self>(value))
+            }
+
+            fn new_value() -> ::std::option::Option<Self> {
+                ::std::option::Option::Some(Self { #(#new_fields,)* })
+            }
+        }
+
+        #paths
+    }
+}
+
+/// The method of a paths builder returning the paths of the field `field_name` of type `field_type`
+fn path_method(
+    method: &syn::Ident,
+    field_name: &str,
+    field_type: &syn::Type,
+) -> proc_macro2::TokenStream {
+    quote! {
+        pub fn #method(&self) -> <#field_type as ::yew_form::paths::FieldPaths<__M>>::Paths {
+            <#field_type as ::yew_form::paths::FieldPaths<__M>>::paths(
+                ::yew_form::join_field_path(&self.path, #field_name).into()
+            )
+        }
+    }
+}
+
+/// The generics of the type with the model `__M` of the field paths prepended
+fn paths_generics(ast: &syn::DeriveInput) -> syn::Generics {
+    let mut generics = ast.generics.clone();
+    generics.params.insert(0, syn::parse_quote!(__M));
+    generics
+}
+
+/// Declares `{Name}Fields`, the builder of the field paths of the type with `methods`,
+/// and implements `FieldPaths` with it
+fn derive_paths(
+    ast: &syn::DeriveInput,
+    methods: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let paths_name = format_ident!("{}Fields", name);
+    let doc = format!("The field paths of [`{}`] in the model `__M`", name);
+    let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let generics = paths_generics(ast);
+    let (paths_impl_generics, paths_ty_generics, _) = generics.split_for_impl();
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #paths_name #paths_impl_generics #where_clause {
+            path: ::yew::virtual_dom::AttrValue,
+            value: ::std::marker::PhantomData<fn() -> (__M, #name #ty_generics)>,
+        }
+
+        impl #paths_impl_generics #paths_name #paths_ty_generics #where_clause {
+            #(#methods)*
+        }
+
+        impl #paths_impl_generics ::std::convert::AsRef<str> for #paths_name #paths_ty_generics #where_clause {
+            fn as_ref(&self) -> &str {
+                &self.path
+            }
+        }
+
+        impl #paths_impl_generics ::yew_form::paths::FieldOf<__M> for #paths_name #paths_ty_generics #where_clause {}
+
+        impl #paths_impl_generics ::yew_form::paths::FieldPaths<__M> for #name #ty_generics #where_clause {
+            type Paths = #paths_name #paths_ty_generics;
+
+            fn paths(path: ::yew::virtual_dom::AttrValue) -> Self::Paths {
+                #paths_name {
+                    path,
+                    value: ::std::marker::PhantomData,
+                }
+            }
+        }
+    }
+}
+
+/// The expression returning the metadata of a field, `field_meta` returns the metadata of its type
+/// which is completed with the `#[validate(...)]` and `#[form(label, help)]` attributes of the field.
+fn meta(
+    field: &syn::Field,
+    attributes: &FormAttributes,
+    field_meta: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let validate = ValidateAttributes::parse(&field.attrs);
+    let mut updates: Vec<proc_macro2::TokenStream> = vec![];
+
+    if validate.required {
+        updates.push(quote! { meta.required = true; });
+    } else if matches!(validate.min_length, Some(min) if min > 0) {
+        // An empty `Option` passes the length rules of `validator`
+        updates.push(quote! { meta.required |= !meta.optional; });
+    }
+    if let Some(min) = validate.min {
+        updates.push(quote! { meta.min = ::std::option::Option::Some(#min); });
+    }
+    if let Some(max) = validate.max {
+        updates.push(quote! { meta.max = ::std::option::Option::Some(#max); });
+    }
+    if let Some(min_length) = validate.min_length {
+        updates.push(quote! { meta.min_length = ::std::option::Option::Some(#min_length); });
+    }
+    if let Some(max_length) = validate.max_length {
+        updates.push(quote! { meta.max_length = ::std::option::Option::Some(#max_length); });
+    }
+    if let Some(regex) = validate.regex {
+        updates.push(quote! {
+            meta.pattern = ::yew_form::FieldMeta::html_pattern(#regex.as_str());
+        });
+    }
+    if let Some(ref label) = attributes.label {
+        updates.push(quote! {
+            meta.label = ::std::option::Option::Some(::yew::virtual_dom::AttrValue::Static(#label));
+        });
+    }
+    if let Some(ref help) = attributes.help {
+        updates.push(quote! {
+            meta.help = ::std::option::Option::Some(::yew::virtual_dom::AttrValue::Static(#help));
+        });
+    }
+
+    if updates.is_empty() {
+        return field_meta;
+    }
+
+    // The attributes describe the field itself, not the values nested in it
+    quote! {
+        {
+            let mut meta = #field_meta?;
+
+            if suffix.is_empty() {
+                #(#updates)*
+            }
+
+            ::std::option::Option::Some(meta)
+        }
+    }
+}
+
+/// A field of an enum variant that is bound to the form
+struct VariantField {
+    ident: syn::Ident,
+    name: String,
+    ty: syn::Type,
+    /// The expression returning the metadata of the field
+    meta: proc_macro2::TokenStream,
+}
+
+/// Implements `FormValue` and `FormEnum` for an enum.
+///
+/// When no variant carries data every variant maps to its name, or the name given with `#[form(rename = "...")]`.
+/// Otherwise the enum is bound as a sub-form: the variant is selected through the `$variant` path
+/// and the fields of the active variant are announced next to it.
+pub fn derive_enum(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let variants = match ast.data {
+        syn::Data::Enum(syn::DataEnum { ref variants, .. }) => variants,
+        _ => panic!("#[derive(FormValue)] can only be used with enums"),
+    };
+
+    let mut variant_idents: Vec<syn::Ident> = vec![];
+    let mut variant_names: Vec<String> = vec![];
+    let mut variant_fields: Vec<Vec<VariantField>> = vec![];
+    // The variants constructed with default values, used when switching variants
+    let mut variant_defaults: Vec<proc_macro2::TokenStream> = vec![];
+
+    for variant in variants {
+        let fields: Vec<&syn::Field> = match variant.fields {
+            syn::Fields::Unit => vec![],
+            syn::Fields::Named(ref fields) => fields.named.iter().collect(),
+            syn::Fields::Unnamed(_) => panic!(
+                "#[derive(FormValue)] variant `{}` cannot have unnamed fields",
+                variant.ident
+            ),
+        };
+
+        let variant_ident = &variant.ident;
+
+        // Fields start from their new value like struct fields, skipped ones from their default
+        let mut skip_idents = vec![];
+        let mut new_idents = vec![];
+        let mut new_types = vec![];
+        for field in &fields {
+            if FormAttributes::parse(&field.attrs).skip {
+                skip_idents.push(&field.ident);
+            } else {
+                new_idents.push(&field.ident);
+                new_types.push(&field.ty);
+            }
+        }
+
+        variant_defaults.push(if new_idents.is_empty() {
+            quote! {
+                ::std::option::Option::Some(Self::#variant_ident {
+                    #(#skip_idents: ::std::default::Default::default()),*
+                })
+            }
+        } else {
+            quote! {
+                match (#(<#new_types>::new_value(),)*) {
+                    (#(::std::option::Option::Some(#new_idents),)*) => ::std::option::Option::Some(Self::#variant_ident {
+                        #(#new_idents,)*
+                        #(#skip_idents: ::std::default::Default::default()),*
+                    }),
+                    _ => ::std::option::Option::None,
+                }
+            }
+        });
+        variant_fields.push(
+            fields
+                .iter()
+                .filter_map(|field| {
+                    let field_ident = field.ident.clone().unwrap();
+                    let attributes = FormAttributes::parse(&field.attrs);
+
+                    if attributes.flatten || attributes.default || attributes.with.is_some() {
+                        panic!(
+                            "#[form(flatten)], #[form(default)] and #[form(with = \"...\")] are not supported on field `{}` of variant `{}`",
+                            field_ident, variant_ident
+                        );
+                    }
+
+                    if attributes.skip {
+                        return None;
+                    }
+
+                    let field_name = attributes
+                        .rename
+                        .clone()
+                        .unwrap_or_else(|| field_ident.to_string());
+
+                    let field_type = &field.ty;
+
+                    Some(VariantField {
+                        meta: meta(field, &attributes, quote! { <#field_type>::field_meta(suffix) }),
+                        ident: field_ident,
+                        name: field_name,
+                        ty: field.ty.clone(),
+                    })
+                })
+                .collect(),
+        );
+
+        let attributes = FormAttributes::parse(&variant.attrs);
+
+        variant_names.push(
+            attributes
+                .rename
+                .unwrap_or_else(|| variant.ident.to_string()),
+        );
+        variant_idents.push(variant.ident.clone());
+    }
+
+    let enum_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let generics = paths_generics(ast);
+    let (paths_impl_generics, _, _) = generics.split_for_impl();
+
+    let has_data = variants
+        .iter()
+        .any(|variant| !matches!(variant.fields, syn::Fields::Unit));
+
+    let mut paths = quote! {
+        impl #paths_impl_generics ::yew_form::paths::FieldPaths<__M> for #enum_name #ty_generics #where_clause {
+            type Paths = ::yew_form::paths::FieldPath<__M, Self>;
+
+            fn paths(path: ::yew::virtual_dom::AttrValue) -> Self::Paths {
+                ::yew_form::paths::FieldPath::new(path)
+            }
+        }
+    };
+
+    let form_value = if !has_data {
+        quote! {
+            fn try_value(&self, field_path: &str) -> ::std::option::Option<::yew::virtual_dom::AttrValue> {
+                field_path.is_empty().then(|| match self {
+                    #(
+                    Self::#variant_idents => #variant_names.into(),
+                    )*
+                })
+            }
+
+            fn set_value(&mut self, field_path: &str, value: &str) -> ::std::result::Result<(), ::yew_form::ConversionError> {
+                if !field_path.is_empty() {
+                    return ::std::result::Result::Err(::yew_form::ConversionError::unknown_field::<Self>(field_path, value));
+                }
+
+                *self = Self::parse_value(value)?;
+                ::std::result::Result::Ok(())
+            }
+
+            fn parse_value(value: &str) -> ::std::result::Result<Self, ::yew_form::ConversionError> {
+                match value {
+                    #(
+                    #variant_names => ::std::result::Result::Ok(Self::#variant_idents),
+                    )*
+                    _ => ::std::result::Result::Err(::yew_form::ConversionError::new(
+                        ::std::any::type_name::<Self>(),
+                        value.to_owned(),
+                        "unknown_variant",
+                    )),
+                }
+            }
+
+            fn new_value() -> ::std::option::Option<Self> {
+                ::std::iter::IntoIterator::into_iter([#(Self::#variant_idents),*]).next()
+            }
+
+            fn kind(field_path: &str) -> ::std::option::Option<::yew_form::model::ValueKind> {
+                field_path.is_empty().then_some(::yew_form::model::ValueKind::Options)
+            }
+
+            fn field_meta(field_path: &str) -> ::std::option::Option<::yew_form::FieldMeta> {
+                field_path.is_empty().then(|| ::yew_form::FieldMeta {
+                    options: &[#(#variant_names),*],
+                    ..::yew_form::FieldMeta::new(::yew_form::model::ValueKind::Options)
+                })
+            }
+        }
+    } else {
+        // Fields with the same name in several variants take the paths of the first one
+        let mut names: Vec<&String> = vec![];
+        let mut methods = vec![quote! {
+            pub fn variant_path(&self) -> ::yew_form::paths::FieldPath<__M, ::yew::virtual_dom::AttrValue> {
+                ::yew_form::paths::FieldPath::new(
+                    ::yew_form::join_field_path(&self.path, ::yew_form::model::VARIANT_FIELD)
+                )
+            }
+        }];
+
+        for field in variant_fields.iter().flatten() {
+            if field.ident == "variant_path" {
+                panic!(
+                    "#[derive(FormValue)] field `variant_path` of enum `{}` clashes with the path of the variant",
+                    enum_name
+                );
+            }
+
+            if !names.contains(&&field.name) {
+                names.push(&field.name);
+                methods.push(path_method(&field.ident, &field.name, &field.ty));
+            }
+        }
+
+        paths = derive_paths(ast, &methods);
+
+        derive_union(
+            &variant_idents,
+            &variant_names,
+            &variant_fields,
+            &variant_defaults,
+        )
+    };
+
+    quote! {
+        impl #impl_generics ::yew_form::model::FormValue for #enum_name #ty_generics #where_clause {
+            #form_value
+        }
+
+        impl #impl_generics ::yew_form::model::FormEnum for #enum_name #ty_generics #where_clause {
+            fn variants() -> &'static [&'static str] {
+                &[#(#variant_names),*]
+            }
+        }
+
+        #paths
+    }
+}
+
+fn derive_union(
+    variant_idents: &[syn::Ident],
+    variant_names: &[String],
+    variant_fields: &[Vec<VariantField>],
+    defaults: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    // Every variant is matched with its fields bound to `__field0`, `__field1`, ...
+    // so they can't clash with the arguments of the generated functions.
+    let patterns: Vec<proc_macro2::TokenStream> = variant_idents
+        .iter()
+        .zip(variant_fields)
+        .map(|(variant_ident, fields)| {
+            let field_idents = fields.iter().map(|field| &field.ident);
+            let bindings = (0..fields.len()).map(|i| format_ident!("__field{}", i));
+
+            quote! { Self::#variant_ident { #(#field_idents: #bindings,)* .. } }
+        })
+        .collect();
+    let bindings: Vec<Vec<syn::Ident>> = variant_fields
+        .iter()
+        .map(|fields| {
+            (0..fields.len())
+                .map(|i| format_ident!("__field{}", i))
+                .collect()
+        })
+        .collect();
+    let field_names: Vec<Vec<&String>> = variant_fields
+        .iter()
+        .map(|fields| fields.iter().map(|field| &field.name).collect())
+        .collect();
+    // Fields with the same name in several variants take the kind and metadata of the first one
+    let mut kind_names: Vec<&String> = vec![];
+    let mut kind_types: Vec<&syn::Type> = vec![];
+    let mut kind_metas: Vec<&proc_macro2::TokenStream> = vec![];
+
+    for field in variant_fields.iter().flatten() {
+        if !kind_names.contains(&&field.name) {
+            kind_names.push(&field.name);
+            kind_types.push(&field.ty);
+            kind_metas.push(&field.meta);
+        }
+    }
+
+    let first_default = &defaults[0];
+
+    quote! {
+        fn fields(&self, prefix: &str, fields: &mut ::std::vec::Vec<::yew::virtual_dom::AttrValue>) {
+            fields.push(::yew_form::join_field_path(prefix, ::yew_form::model::VARIANT_FIELD).into());
+
+            match self {
+                #(
+                #patterns => {
+                    #(
+                    #bindings.fields(&::yew_form::join_field_path(prefix, #field_names), fields);
+                    )*
+                }
+                )*
+            }
+        }
+
+        fn try_value(&self, field_path: &str) -> ::std::option::Option<::yew::virtual_dom::AttrValue> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            if field_name == ::yew_form::model::VARIANT_FIELD {
+                return suffix.is_empty().then(|| match self {
+                    #(
+                    #patterns => #variant_names.into(),
+                    )*
+                });
+            }
+
+            match self {
+                #(
+                #patterns => match field_name {
+                    #(
+                    #field_names => #bindings.try_value(suffix),
+                    )*
+                    _ => ::std::option::Option::None,
+                },
+                )*
+            }
+        }
+
+        fn set_value(&mut self, field_path: &str, value: &str) -> ::std::result::Result<(), ::yew_form::ConversionError> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            if field_name == ::yew_form::model::VARIANT_FIELD {
+                if !suffix.is_empty() {
+                    return ::std::result::Result::Err(::yew_form::ConversionError::unknown_field::<Self>(field_path, value));
+                }
+
+                // Keep the values of the active variant when it is selected again
+                if self.value(field_path) != value {
+                    *self = match value {
+                        #(
+                        #variant_names => #defaults.ok_or_else(|| ::yew_form::ConversionError::new(
+                            ::std::any::type_name::<Self>(),
+                            value.to_owned(),
+                            "no_new_value",
+                        ))?,
+                        )*
+                        _ => return ::std::result::Result::Err(::yew_form::ConversionError::new(
+                            ::std::any::type_name::<Self>(),
+                            value.to_owned(),
+                            "unknown_variant",
+                        )),
+                    };
+                }
+
+                return ::std::result::Result::Ok(());
+            }
+
+            match self {
+                #(
+                #patterns => match field_name {
+                    #(
+                    #field_names => #bindings
+                        .set_value(suffix, value)
+                        .map_err(|e| e.prefixed(#field_names)),
+                    )*
+                    _ => ::std::result::Result::Err(::yew_form::ConversionError::unknown_field::<Self>(field_path, value)),
+                },
+                )*
+            }
+        }
+
+        fn has_field(&self, field_name: &str) -> bool {
+            field_name == ::yew_form::model::VARIANT_FIELD
+                || match self {
+                    #(
+                    Self::#variant_idents { .. } => [#(#field_names),*].contains(&field_name),
+                    )*
+                }
+        }
+
+        fn list_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut dyn ::yew_form::model::FormList> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            match self {
+                #(
+                #patterns => match field_name {
+                    #(
+                    #field_names => #bindings.list_mut(suffix),
+                    )*
+                    _ => ::std::option::Option::None,
+                },
+                )*
+            }
+        }
+
+        fn map_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut dyn ::yew_form::model::FormMap> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            match self {
+                #(
+                #patterns => match field_name {
+                    #(
+                    #field_names => #bindings.map_mut(suffix),
+                    )*
+                    _ => ::std::option::Option::None,
+                },
+                )*
+            }
+        }
+
+        fn files(&self, field_path: &str) -> ::std::option::Option<&::yew_form::files::FormFiles> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            match self {
+                #(
+                #patterns => match field_name {
+                    #(
+                    #field_names => ::yew_form::model::FormValue::files(#bindings, suffix),
+                    )*
+                    _ => ::std::option::Option::None,
+                },
+                )*
+            }
+        }
+
+        fn files_mut(&mut self, field_path: &str) -> ::std::option::Option<&mut ::yew_form::files::FormFiles> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            match self {
+                #(
+                #patterns => match field_name {
+                    #(
+                    #field_names => #bindings.files_mut(suffix),
+                    )*
+                    _ => ::std::option::Option::None,
+                },
+                )*
+            }
+        }
+
+        fn kind(field_path: &str) -> ::std::option::Option<::yew_form::model::ValueKind> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            match field_name {
+                ::yew_form::model::VARIANT_FIELD => ::std::option::Option::Some(::yew_form::model::ValueKind::Options),
+                #(
+                #kind_names => <#kind_types>::kind(suffix),
+                )*
+                _ => ::std::option::Option::None,
+            }
+        }
+
+        fn field_meta(field_path: &str) -> ::std::option::Option<::yew_form::FieldMeta> {
+            let (field_name, suffix) = ::yew_form::split_field_path(field_path);
+
+            match field_name {
+                ::yew_form::model::VARIANT_FIELD => ::std::option::Option::Some(::yew_form::FieldMeta {
+                    options: &[#(#variant_names),*],
+                    ..::yew_form::FieldMeta::new(::yew_form::model::ValueKind::Options)
+                }),
+                #(
+                #kind_names => #kind_metas,
+                )*
+                _ => ::std::option::Option::None,
+            }
+        }
+
+        fn parse_value(value: &str) -> ::std::result::Result<Self, ::yew_form::ConversionError> {
+            ::std::result::Result::Err(::yew_form::ConversionError::invalid::<Self>(value))
+        }
+
+        fn new_value() -> ::std::option::Option<Self> {
+            #first_default
+        }
+    }
+}